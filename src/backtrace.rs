@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::backtrace::Backtrace;
+use std::fmt;
+
+/// Logs the backtrace of the current thread, after an optional message.  Useful to find out who
+/// called a function when you cannot attach a debugger.
+///
+/// The backtrace is always captured, regardless of `RUST_BACKTRACE`.  Function names are only
+/// available if the binary has debug information.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_bt;
+/// # let path = "/";
+/// #
+/// ddbg_bt!("/tmp/log");
+/// ddbg_bt!("/tmp/log", "who deleted {}?", path);
+/// ```
+#[macro_export]
+macro_rules! ddbg_bt {
+    ($uri:expr $(,)?) => {{
+        $crate::dirty_log_backtrace(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::format_args!("backtrace"),
+        );
+    }};
+    ($uri:expr, $($arg:tt)+) => {{
+        $crate::dirty_log_backtrace(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::format_args!($($arg)+),
+        );
+    }};
+}
+
+/// Logs a `ddbg_bt!()`.
+#[doc(hidden)]
+pub fn dirty_log_backtrace(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    args: fmt::Arguments<'_>,
+) {
+    let backtrace = Backtrace::force_capture();
+
+    crate::dirty_log_message(uri, file, line, format_args!("{args}\n{backtrace}"));
+}
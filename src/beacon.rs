@@ -0,0 +1,100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt::Write;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+fn beacon_payload() -> String {
+    let mut payload = String::with_capacity(256);
+
+    payload.push_str("ddbg-beacon 1\n");
+    let _ = writeln!(payload, "name={}", crate::process::name());
+    let _ = writeln!(payload, "pid={}", std::process::id());
+
+    for uri in crate::sink::active_uris() {
+        let _ = writeln!(payload, "sink={uri}");
+    }
+
+    payload
+}
+
+/// Starts a background thread that periodically sends a UDP beacon announcing this process, until
+/// the returned [`Beacon`] is dropped.  The beacon is a small text datagram with the name of the
+/// process, its pid, and the sinks it is currently logging to:
+///
+/// ```text
+/// ddbg-beacon 1
+/// name=my-program
+/// pid=1234
+/// sink=tcp://192.168.1.42:12345
+/// ```
+///
+/// The target can be a broadcast address, so that it is easy to find out which processes in the
+/// local network are producing debug output.  Run `ddbg-listen --beacons <port>` to see them.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use std::net::{Ipv4Addr, SocketAddr};
+/// # use std::time::Duration;
+/// #
+/// let _beacon = dirty_debug::start_beacon(
+///     SocketAddr::from((Ipv4Addr::BROADCAST, 5299)),
+///     Duration::from_secs(5),
+/// );
+/// ```
+///
+/// # Panics
+///
+/// If the beacon socket or thread cannot be created.
+pub fn start_beacon(target: SocketAddr, interval: Duration) -> Beacon {
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    let start = move || {
+        let bind_address = match target {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+        let socket = UdpSocket::bind(bind_address)?;
+
+        socket.set_broadcast(true)?;
+
+        thread::Builder::new().name("dirty-debug-beacon".to_owned()).spawn(move || loop {
+            // The beacon is best effort, so we just try again in the next interval.
+            let _ = socket.send_to(beacon_payload().as_bytes(), target);
+
+            match stopped.recv_timeout(interval) {
+                Err(mpsc::RecvTimeoutError::Timeout) => (),
+                Ok(()) | Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        })
+    };
+
+    match start() {
+        Ok(thread) => Beacon { stop: Some(stop), thread: Some(thread) },
+        Err(e) => panic!("failed to start beacon to \"{target}\": {e}"),
+    }
+}
+
+/// Stops the beacon of [`start_beacon()`] when dropped.
+#[must_use = "the beacon stops when it is dropped"]
+pub struct Beacon {
+    /// Dropping this wakes the thread up so that it stops.
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Beacon {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,373 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![cfg_attr(feature = "fatal-warnings", deny(warnings))]
+
+//! Exports logs to an Avro object container file, so they can be analyzed with dataframe tools
+//! (e.g. pandas, polars, or spark).  The inputs can be log files or `merge://` directories.  Every
+//! message becomes a row with these columns:
+//!
+//! * `timestamp_us` — Microseconds since the unix epoch (only known for `merge://` logs).
+//! * `source` — The name of the file the message came from, e.g. `my-program.1234`.
+//! * `file` and `line` — The call site (null for messages of dirty-debug itself).
+//! * `message` — The message.
+//!
+//! Usage: `ddbg-avro <output.avro> <log file or merge dir>...`
+
+use std::ffi::OsStr;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = r#"{"type":"record","name":"Message","namespace":"dirty_debug","fields":[
+{"name":"timestamp_us","type":["null",{"type":"long","logicalType":"timestamp-micros"}]},
+{"name":"source","type":"string"},
+{"name":"file","type":["null","string"]},
+{"name":"line","type":["null","int"]},
+{"name":"message","type":"string"}]}"#;
+
+const ROWS_PER_BLOCK: usize = 4096;
+
+struct Row {
+    timestamp_us: Option<i64>,
+    source: String,
+    file: Option<String>,
+    line: Option<i32>,
+    message: String,
+}
+
+fn push_long(buffer: &mut Vec<u8>, n: i64) {
+    // Zigzag encoding followed by a variable-length encoding.
+    let mut n = u64::from_ne_bytes(((n << 1) ^ (n >> 63)).to_ne_bytes());
+
+    while n >= 0x80 {
+        buffer.push(u8::try_from(n & 0x7f).unwrap_or_default() | 0x80);
+        n >>= 7;
+    }
+
+    buffer.push(u8::try_from(n).unwrap_or_default());
+}
+
+fn push_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    push_long(buffer, i64::try_from(bytes.len()).unwrap_or(i64::MAX));
+    buffer.extend_from_slice(bytes);
+}
+
+/// Encodes a value of a `["null", T]` union.
+fn push_optional<T>(buffer: &mut Vec<u8>, value: Option<T>, push: impl FnOnce(&mut Vec<u8>, T)) {
+    match value {
+        None => push_long(buffer, 0),
+        Some(value) => {
+            push_long(buffer, 1);
+            push(buffer, value);
+        }
+    }
+}
+
+fn push_row(buffer: &mut Vec<u8>, row: &Row) {
+    push_optional(buffer, row.timestamp_us, push_long);
+    push_bytes(buffer, row.source.as_bytes());
+    push_optional(buffer, row.file.as_deref(), |b, file| push_bytes(b, file.as_bytes()));
+    push_optional(buffer, row.line, |b, line| push_long(b, i64::from(line)));
+    push_bytes(buffer, row.message.as_bytes());
+}
+
+fn write_avro(output: &mut impl Write, rows: &[Row]) -> std::io::Result<()> {
+    let random = RandomState::new();
+    let mut sync_marker: [u8; 16] = [0; 16];
+
+    sync_marker[0..8].copy_from_slice(&random.hash_one(0).to_le_bytes());
+    sync_marker[8..16].copy_from_slice(&random.hash_one(1).to_le_bytes());
+
+    let mut header: Vec<u8> = b"Obj\x01".to_vec();
+
+    // The metadata map, with a single block of two entries.
+    push_long(&mut header, 2);
+    push_bytes(&mut header, b"avro.schema");
+    push_bytes(&mut header, SCHEMA.replace('\n', "").as_bytes());
+    push_bytes(&mut header, b"avro.codec");
+    push_bytes(&mut header, b"null");
+    push_long(&mut header, 0);
+    header.extend_from_slice(&sync_marker);
+
+    output.write_all(&header)?;
+
+    let mut block: Vec<u8> = Vec::new();
+    let mut block_header: Vec<u8> = Vec::new();
+
+    for chunk in rows.chunks(ROWS_PER_BLOCK) {
+        block.clear();
+        block_header.clear();
+
+        for row in chunk {
+            push_row(&mut block, row);
+        }
+
+        push_long(&mut block_header, i64::try_from(chunk.len()).unwrap_or(i64::MAX));
+        push_long(&mut block_header, i64::try_from(block.len()).unwrap_or(i64::MAX));
+
+        output.write_all(&block_header)?;
+        output.write_all(&block)?;
+        output.write_all(&sync_marker)?;
+    }
+
+    output.flush()
+}
+
+fn parse_timestamp(s: &str) -> Option<i64> {
+    let (secs, nanos) = s.split_once('.')?;
+    let secs: i64 = secs.parse().ok()?;
+    let nanos: i64 = nanos.parse().ok()?;
+
+    Some(secs * 1_000_000 + nanos / 1000)
+}
+
+/// Parses a `[file:line] message` or `[dirty-debug] message` line (without the prefix).
+fn parse_message(text: &str) -> (Option<String>, Option<i32>, &str) {
+    let Some((source_info, message)) = text.strip_prefix('[').and_then(|t| t.split_once("] "))
+    else {
+        return (None, None, text);
+    };
+
+    match source_info.rsplit_once(':').and_then(|(f, l)| Some((f, l.parse().ok()?))) {
+        Some((file, line)) => (Some(file.to_owned()), Some(line), message),
+        None => (None, None, message),
+    }
+}
+
+fn read_rows(path: &Path, timestamped: bool, rows: &mut Vec<Row>) -> std::io::Result<()> {
+    let source = path.file_stem().map_or(String::new(), |s| s.to_string_lossy().into_owned());
+    let content = std::fs::read(path)?;
+    let mut first_row_of_file = true;
+
+    for line in String::from_utf8_lossy(&content).lines() {
+        let (timestamp_us, text) = match timestamped {
+            true => line
+                .split_once(' ')
+                .and_then(|(timestamp, text)| Some((Some(parse_timestamp(timestamp)?), text)))
+                .unwrap_or((None, line)),
+            false => (None, line),
+        };
+        let text = dirty_debug::dirty_skip_prefix(text);
+
+        let is_new_message = timestamp_us.is_some() || text.starts_with('[');
+
+        match rows.last_mut() {
+            // A line that does not start a message is the continuation of a multiline message.
+            Some(last) if !is_new_message && !first_row_of_file => {
+                last.message.push('\n');
+                last.message.push_str(line);
+            }
+            _ => {
+                let (file, line, message) = parse_message(text);
+
+                rows.push(Row {
+                    timestamp_us,
+                    source: source.clone(),
+                    file,
+                    line,
+                    message: message.to_owned(),
+                });
+                first_row_of_file = false;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_input(path: &Path, rows: &mut Vec<Row>) -> std::io::Result<()> {
+    if !path.is_dir() {
+        return read_rows(path, false, rows);
+    }
+
+    let mut filepaths: Vec<PathBuf> = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+
+    filepaths.retain(|filepath| filepath.extension() == Some(OsStr::new("log")));
+    filepaths.sort();
+
+    for filepath in filepaths {
+        read_rows(&filepath, true, rows)?;
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+
+    let [output_filepath, inputs @ ..] = args.as_slice() else {
+        eprintln!("usage: ddbg-avro <output.avro> <log file or merge dir>...");
+        std::process::exit(1);
+    };
+
+    let mut rows: Vec<Row> = Vec::new();
+
+    for input in inputs {
+        if let Err(e) = read_input(input, &mut rows) {
+            eprintln!("failed to read \"{}\": {e}", input.display());
+            std::process::exit(1);
+        }
+    }
+
+    let result = std::fs::File::create(output_filepath)
+        .and_then(|file| write_avro(&mut BufWriter::new(file), &rows));
+
+    if let Err(e) = result {
+        eprintln!("failed to write \"{}\": {e}", output_filepath.display());
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Decodes the values of the Avro encoding, as a reader of the file would.
+    struct Decoder<'a> {
+        data: &'a [u8],
+    }
+
+    impl<'a> Decoder<'a> {
+        fn long(&mut self) -> i64 {
+            let mut n: u64 = 0;
+            let mut shift = 0;
+
+            loop {
+                let (&byte, rest) = self.data.split_first().unwrap();
+
+                self.data = rest;
+                n |= u64::from(byte & 0x7f) << shift;
+                shift += 7;
+
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+
+            i64::from_ne_bytes((n >> 1).to_ne_bytes()) ^ -i64::from_ne_bytes((n & 1).to_ne_bytes())
+        }
+
+        fn fixed(&mut self, len: usize) -> &'a [u8] {
+            let (fixed, rest) = self.data.split_at(len);
+
+            self.data = rest;
+            fixed
+        }
+
+        fn bytes(&mut self) -> &'a [u8] {
+            let len = usize::try_from(self.long()).unwrap();
+
+            self.fixed(len)
+        }
+
+        fn string(&mut self) -> &'a str {
+            std::str::from_utf8(self.bytes()).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_push_long() {
+        let encode = |n: i64| {
+            let mut buffer: Vec<u8> = Vec::new();
+
+            push_long(&mut buffer, n);
+            buffer
+        };
+
+        assert_eq!(encode(0), [0x00]);
+        assert_eq!(encode(-1), [0x01]);
+        assert_eq!(encode(1), [0x02]);
+        assert_eq!(encode(63), [0x7e]);
+        assert_eq!(encode(64), [0x80, 0x01]);
+        assert_eq!(encode(-65), [0x81, 0x01]);
+
+        for n in [i64::MIN, -300, 300, i64::MAX] {
+            assert_eq!(Decoder { data: &encode(n) }.long(), n);
+        }
+    }
+
+    #[test]
+    fn test_write_avro() {
+        let filepath =
+            std::env::temp_dir().join(format!("ddbg-avro-test-{}.log", std::process::id()));
+
+        std::fs::write(
+            &filepath,
+            "[src/main.rs:7] two\nlines\n[dirty-debug] opening \"/tmp/log\" took 1.00ms\n",
+        )
+        .unwrap();
+
+        let mut rows: Vec<Row> = Vec::new();
+
+        read_input(&filepath, &mut rows).unwrap();
+        std::fs::remove_file(&filepath).unwrap();
+
+        let mut avro: Vec<u8> = Vec::new();
+
+        write_avro(&mut avro, &rows).unwrap();
+
+        let mut decoder = Decoder { data: &avro };
+        let source = filepath.file_stem().unwrap().to_str().unwrap();
+
+        // The header: magic, metadata and sync marker.
+        assert_eq!(decoder.fixed(4), b"Obj\x01");
+        assert_eq!(decoder.long(), 2);
+        assert_eq!(decoder.string(), "avro.schema");
+        assert_eq!(decoder.string(), SCHEMA.replace('\n', ""));
+        assert_eq!(decoder.string(), "avro.codec");
+        assert_eq!(decoder.string(), "null");
+        assert_eq!(decoder.long(), 0);
+
+        let sync_marker = decoder.fixed(16);
+
+        // A single block with both rows.
+        assert_eq!(decoder.long(), 2);
+        assert_eq!(decoder.long(), i64::try_from(decoder.data.len() - 16).unwrap());
+
+        assert_eq!(decoder.long(), 0);
+        assert_eq!(decoder.string(), source);
+        assert_eq!(decoder.long(), 1);
+        assert_eq!(decoder.string(), "src/main.rs");
+        assert_eq!(decoder.long(), 1);
+        assert_eq!(decoder.long(), 7);
+        assert_eq!(decoder.string(), "two\nlines");
+
+        assert_eq!(decoder.long(), 0);
+        assert_eq!(decoder.string(), source);
+        assert_eq!(decoder.long(), 0);
+        assert_eq!(decoder.long(), 0);
+        assert_eq!(decoder.string(), "opening \"/tmp/log\" took 1.00ms");
+
+        assert_eq!(decoder.fixed(16), sync_marker);
+        assert!(decoder.data.is_empty());
+    }
+
+    #[test]
+    fn test_read_rows_with_prefix() {
+        let filepath =
+            std::env::temp_dir().join(format!("ddbg-avro-prefix-test-{}.log", std::process::id()));
+
+        std::fs::write(
+            &filepath,
+            "2026-10-16T12:34:56.123456Z [server pid=42] [src/main.rs:7] two\n\
+             lines\n\
+             2026-10-16T12:34:56.234567Z [server pid=42] [dirty-debug] bye\n",
+        )
+        .unwrap();
+
+        let mut rows: Vec<Row> = Vec::new();
+
+        read_rows(&filepath, false, &mut rows).unwrap();
+        std::fs::remove_file(&filepath).unwrap();
+
+        let rows: Vec<_> =
+            rows.iter().map(|row| (row.file.as_deref(), row.line, row.message.as_str())).collect();
+
+        assert_eq!(rows, [(Some("src/main.rs"), Some(7), "two\nlines"), (None, None, "bye")]);
+    }
+}
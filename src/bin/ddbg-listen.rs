@@ -0,0 +1,504 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![cfg_attr(feature = "fatal-warnings", deny(warnings))]
+
+//! Listens for `tcp://` connections and prints what they log.
+//!
+//! With `--compare` it accepts two connections (e.g. the old and the new build of a program,
+//! running at the same time) and shows them side by side, the first on the left:
+//!
+//! * By default lines are shown as they arrive, so the two logs are aligned by time.
+//! * With `--by-message` the n-th line of the left is shown next to the n-th line of the right, so
+//!   the same sequence of events lines up.  Rows where the lines come from different call sites
+//!   are marked with `≠`.
+//!
+//! The width of the output is taken from the `COLUMNS` environment variable.
+//!
+//! With `--udp` it receives the datagrams of `udp://` instead.  Use `--join=<group>` to join a
+//! multicast group.
+//!
+//! With `--beacons` it receives the beacons of `dirty_debug::start_beacon()` and prints the name,
+//! pid and destinations of every process that sends them, whenever a process shows up or its
+//! destinations change.
+//!
+//! With `--auto=<name>` instead of a port it listens on a free port and writes it to the
+//! rendezvous file `ddbg-rendezvous-<name>` in the temporary directory, where `tcp-auto://<name>`
+//! finds it.
+//!
+//! Usage: `ddbg-listen [--compare [--by-message]] <port>|--auto=<name>` or
+//! `ddbg-listen --udp [--join=<group>] <port>` or `ddbg-listen --beacons <port>`
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::sync::mpsc;
+
+const DEFAULT_WIDTH: usize = 160;
+
+enum Event {
+    Connected { connection: usize, peer: SocketAddr },
+    Line { connection: usize, line: String },
+    Disconnected { connection: usize },
+}
+
+fn spawn_reader(connection: usize, stream: TcpStream, events: mpsc::Sender<Event>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+
+            if events.send(Event::Line { connection, line }).is_err() {
+                return;
+            }
+        }
+
+        let _ = events.send(Event::Disconnected { connection });
+    });
+}
+
+/// Truncates or pads `s` to exactly `width` characters.
+fn fit(s: &str, width: usize) -> String {
+    let mut fitted: String = match s.chars().count() > width {
+        true => s.chars().take(width.saturating_sub(1)).chain((width > 0).then_some('…')).collect(),
+        false => s.to_owned(),
+    };
+    let len = fitted.chars().count();
+
+    fitted.extend(std::iter::repeat(' ').take(width.saturating_sub(len)));
+    fitted
+}
+
+/// The `[file:line]` of a line, after its prefix (e.g. a timestamp).
+fn call_site(line: &str) -> Option<&str> {
+    dirty_debug::dirty_skip_prefix(line)
+        .strip_prefix('[')
+        .and_then(|l| l.split_once(']'))
+        .map(|(call_site, _)| call_site)
+}
+
+/// The width of each side of `--compare` when the output is `width` characters wide.
+fn column_width(width: usize) -> usize {
+    width.saturating_sub(3) / 2
+}
+
+fn format_row(left: &str, right: &str, separator: char, column_width: usize) -> String {
+    format!("{} {separator} {}", fit(left, column_width), fit(right, column_width).trim_end())
+}
+
+fn print_row(left: &str, right: &str, separator: char, column_width: usize) {
+    println!("{}", format_row(left, right, separator, column_width));
+}
+
+/// Takes the rows of `--compare --by-message` that can be shown, i.e. the n-th lines of both
+/// sides, plus the rest of a side once the other one disconnected.  Each row has its separator.
+fn take_rows(
+    pending: &mut [VecDeque<String>; 2],
+    connected: [bool; 2],
+) -> Vec<(String, String, char)> {
+    let mut rows: Vec<(String, String, char)> = Vec::new();
+
+    // Once a side disconnects there is nothing to wait for, so we show the rest of the other.
+    while let (Some(_), _) | (_, Some(_)) = (pending[0].front(), pending[1].front()) {
+        let (left, right) = match (pending[0].front(), pending[1].front()) {
+            (Some(_), Some(_)) => (pending[0].pop_front(), pending[1].pop_front()),
+            (Some(_), None) if !connected[1] => (pending[0].pop_front(), None),
+            (None, Some(_)) if !connected[0] => (None, pending[1].pop_front()),
+            _ => break,
+        };
+        let left = left.unwrap_or_default();
+        let right = right.unwrap_or_default();
+        let separator = match call_site(&left) == call_site(&right) {
+            true => '|',
+            false => '≠',
+        };
+
+        rows.push((left, right, separator));
+    }
+
+    rows
+}
+
+fn listen(listener: &TcpListener) {
+    let (events_sender, events) = mpsc::channel();
+    let mut peers: HashMap<usize, SocketAddr> = HashMap::new();
+
+    std::thread::spawn({
+        let listener = listener.try_clone().expect("failed to clone listener");
+
+        move || {
+            for (connection, stream) in listener.incoming().flatten().enumerate() {
+                if let Ok(peer) = stream.peer_addr() {
+                    let _ = events_sender.send(Event::Connected { connection, peer });
+                }
+
+                spawn_reader(connection, stream, events_sender.clone());
+            }
+        }
+    });
+
+    for event in events {
+        match event {
+            Event::Connected { connection, peer } => {
+                eprintln!("ddbg-listen: {peer} connected");
+                peers.insert(connection, peer);
+            }
+            Event::Line { connection, line } => match peers.get(&connection) {
+                Some(peer) => println!("{peer}: {line}"),
+                None => println!("{line}"),
+            },
+            Event::Disconnected { connection } => {
+                if let Some(peer) = peers.remove(&connection) {
+                    eprintln!("ddbg-listen: {peer} disconnected");
+                }
+            }
+        }
+    }
+}
+
+fn compare(listener: &TcpListener, by_message: bool) {
+    let (events_sender, events) = mpsc::channel();
+
+    for connection in 0..2 {
+        let Ok((stream, peer)) = listener.accept() else {
+            eprintln!("ddbg-listen: failed to accept connection");
+            std::process::exit(1);
+        };
+
+        eprintln!("ddbg-listen: {} is {peer}", ["left", "right"][connection]);
+        spawn_reader(connection, stream, events_sender.clone());
+    }
+
+    drop(events_sender);
+
+    let width: usize =
+        std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()).unwrap_or(DEFAULT_WIDTH);
+    let column_width = column_width(width);
+    let mut pending: [VecDeque<String>; 2] = [VecDeque::new(), VecDeque::new()];
+    let mut connected: [bool; 2] = [true, true];
+
+    for event in events {
+        match event {
+            Event::Line { connection, line } if !by_message => match connection {
+                0 => print_row(&line, "", '|', column_width),
+                _ => print_row("", &line, '|', column_width),
+            },
+            Event::Line { connection, line } => pending[connection].push_back(line),
+            Event::Connected { .. } => (),
+            Event::Disconnected { connection } => connected[connection] = false,
+        }
+
+        for (left, right, separator) in take_rows(&mut pending, connected) {
+            print_row(&left, &right, separator, column_width);
+        }
+    }
+}
+
+fn bind_udp(port: u16, group: Option<IpAddr>) -> std::io::Result<UdpSocket> {
+    let socket = match group {
+        Some(IpAddr::V6(group)) => {
+            let socket = UdpSocket::bind((Ipv6Addr::UNSPECIFIED, port))?;
+            socket.join_multicast_v6(&group, 0)?;
+            socket
+        }
+        Some(IpAddr::V4(group)) => {
+            let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+            socket.join_multicast_v4(&group, &Ipv4Addr::UNSPECIFIED)?;
+            socket
+        }
+        None => UdpSocket::bind((Ipv6Addr::UNSPECIFIED, port))
+            .or_else(|_| UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)))?,
+    };
+
+    Ok(socket)
+}
+
+/// The lines of a datagram of `udp://`, each with the peer that sent it.
+fn datagram_lines(peer: SocketAddr, datagram: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(datagram).lines().map(|line| format!("{peer}: {line}")).collect()
+}
+
+fn listen_udp(port: u16, group: Option<IpAddr>) -> std::io::Result<()> {
+    let socket = bind_udp(port, group)?;
+    let mut datagram: Vec<u8> = vec![0; 65536];
+
+    loop {
+        let (len, peer) = socket.recv_from(&mut datagram)?;
+
+        for line in datagram_lines(peer, &datagram[..len]) {
+            println!("{line}");
+        }
+    }
+}
+
+/// A process announced by a beacon of `dirty_debug::start_beacon()`.
+#[derive(Debug, PartialEq, Eq)]
+struct Beacon<'a> {
+    name: &'a str,
+    pid: u32,
+    sinks: Vec<&'a str>,
+}
+
+fn parse_beacon(payload: &str) -> Option<Beacon<'_>> {
+    let mut lines = payload.lines();
+
+    if lines.next()? != "ddbg-beacon 1" {
+        return None;
+    }
+
+    let mut name: Option<&str> = None;
+    let mut pid: Option<u32> = None;
+    let mut sinks: Vec<&str> = Vec::new();
+
+    for line in lines {
+        match line.split_once('=')? {
+            ("name", value) => name = Some(value),
+            ("pid", value) => pid = Some(value.parse().ok()?),
+            ("sink", value) => sinks.push(value),
+            // Fields added by later versions.
+            _ => (),
+        }
+    }
+
+    Some(Beacon { name: name?, pid: pid?, sinks })
+}
+
+fn format_beacon(ip: IpAddr, beacon: &Beacon<'_>) -> String {
+    let Beacon { name, pid, sinks } = beacon;
+
+    match sinks.is_empty() {
+        true => format!("{ip}: {name} (pid {pid}) is not logging"),
+        false => format!("{ip}: {name} (pid {pid}) is logging to {}", sinks.join(", ")),
+    }
+}
+
+fn listen_beacons(port: u16) -> std::io::Result<()> {
+    let socket = bind_udp(port, None)?;
+    let mut datagram: Vec<u8> = vec![0; 65536];
+    // The last beacon of every process, so that we only print what changed.
+    let mut last_beacons: HashMap<(IpAddr, u32), String> = HashMap::new();
+
+    loop {
+        let (len, peer) = socket.recv_from(&mut datagram)?;
+        let payload = String::from_utf8_lossy(&datagram[..len]);
+
+        let Some(beacon) = parse_beacon(&payload) else {
+            eprintln!("{peer}: ignoring a datagram that is not a beacon");
+            continue;
+        };
+
+        let line = format_beacon(peer.ip(), &beacon);
+
+        if last_beacons.get(&(peer.ip(), beacon.pid)) != Some(&line) {
+            println!("{line}");
+            last_beacons.insert((peer.ip(), beacon.pid), line);
+        }
+    }
+}
+
+fn rendezvous_filepath(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("ddbg-rendezvous-{name}"))
+}
+
+/// Writes the port we are listening on to the rendezvous file of `name`, for `tcp-auto://<name>`.
+fn announce(listener: &TcpListener, name: &str) -> std::io::Result<()> {
+    let port = listener.local_addr()?.port();
+    let filepath = rendezvous_filepath(name);
+
+    std::fs::write(&filepath, format!("{port}\n"))?;
+    eprintln!("listening on port {port} (announced in \"{}\")", filepath.display());
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let port: Option<u16> =
+        args.iter().find(|arg| !arg.starts_with("--")).and_then(|p| p.parse().ok());
+
+    let group: Option<Result<IpAddr, _>> =
+        args.iter().find_map(|arg| arg.strip_prefix("--join=")).map(str::parse);
+
+    let auto: Option<&str> = args.iter().find_map(|arg| arg.strip_prefix("--auto="));
+
+    let (Some(port), None | Some(Ok(_))) = (port.or(auto.map(|_| 0)), &group) else {
+        eprintln!("usage: ddbg-listen [--compare [--by-message]] <port>|--auto=<name>");
+        eprintln!("       ddbg-listen --udp [--join=<group>] <port>");
+        eprintln!("       ddbg-listen --beacons <port>");
+        std::process::exit(1);
+    };
+
+    if flag("--beacons") {
+        if let Err(e) = listen_beacons(port) {
+            eprintln!("failed to receive beacons on port {port}: {e}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if flag("--udp") {
+        if let Err(e) = listen_udp(port, group.and_then(Result::ok)) {
+            eprintln!("failed to receive on port {port}: {e}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let listener = match TcpListener::bind((Ipv6Addr::UNSPECIFIED, port))
+        .or_else(|_| TcpListener::bind(("0.0.0.0", port)))
+    {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("failed to listen on port {port}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(name) = auto {
+        if let Err(e) = announce(&listener, name) {
+            eprintln!("failed to write the rendezvous file of \"{name}\": {e}");
+            std::process::exit(1);
+        }
+    }
+
+    match flag("--compare") {
+        true => compare(&listener, flag("--by-message")),
+        false => listen(&listener),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcde", 5), "abcde");
+        assert_eq!(fit("abcdef", 5), "abcd…");
+        assert_eq!(fit("≠≠≠≠≠≠", 3), "≠≠…");
+        assert_eq!(fit("abc", 0), "");
+    }
+
+    #[test]
+    fn test_format_row() {
+        assert_eq!(column_width(23), 10);
+        assert_eq!(column_width(2), 0);
+        assert_eq!(
+            format_row("[a.rs:1] left", "[a.rs:1] right", '|', 10),
+            "[a.rs:1] … | [a.rs:1] …",
+        );
+        assert_eq!(format_row("left", "", '|', 10), "left       | ");
+        assert_eq!(format_row("", "right", '|', 10), "           | right");
+    }
+
+    #[test]
+    fn test_call_site() {
+        assert_eq!(call_site("[src/main.rs:7] hello"), Some("src/main.rs:7"));
+        assert_eq!(
+            call_site("+1.000000s [main tid=7] [src/main.rs:7] hello"),
+            Some("src/main.rs:7")
+        );
+        assert_eq!(
+            call_site("2026-10-16T12:34:56.123456Z [server pid=42] [dirty-debug] bye"),
+            Some("dirty-debug"),
+        );
+        assert_eq!(call_site("continuation"), None);
+    }
+
+    #[test]
+    fn test_take_rows() {
+        let mut pending: [VecDeque<String>; 2] = [
+            ["[a.rs:1] x", "[a.rs:2] y", "[a.rs:3] z"].map(str::to_owned).into(),
+            ["[a.rs:1] x", "[b.rs:9] w"].map(str::to_owned).into(),
+        ];
+        let row = |left: &str, right: &str, separator: char| {
+            (left.to_owned(), right.to_owned(), separator)
+        };
+
+        assert_eq!(
+            take_rows(&mut pending, [true, true]),
+            [row("[a.rs:1] x", "[a.rs:1] x", '|'), row("[a.rs:2] y", "[b.rs:9] w", '≠')],
+        );
+
+        // The left line waits for its counterpart, until the right side disconnects.
+        assert_eq!(take_rows(&mut pending, [true, true]), []);
+        assert_eq!(take_rows(&mut pending, [true, false]), [row("[a.rs:3] z", "", '≠')]);
+        assert!(pending.iter().all(VecDeque::is_empty));
+
+        pending[1].push_back("[dirty-debug] bye".to_owned());
+
+        assert_eq!(take_rows(&mut pending, [false, true]), [row("", "[dirty-debug] bye", '≠')]);
+    }
+
+    #[test]
+    fn test_udp() {
+        let socket = bind_udp(0, None).unwrap();
+        let local = socket.local_addr().unwrap();
+        let loopback: IpAddr = match local {
+            SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+            SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+        };
+        let sender = UdpSocket::bind(SocketAddr::new(loopback, 0)).unwrap();
+        let mut datagram: Vec<u8> = vec![0; 65536];
+
+        sender.send_to(b"[a.rs:1] one\ntwo\n", (loopback, local.port())).unwrap();
+
+        let (len, peer) = socket.recv_from(&mut datagram).unwrap();
+
+        assert_eq!(peer.port(), sender.local_addr().unwrap().port());
+        assert_eq!(
+            datagram_lines(peer, &datagram[..len]),
+            [format!("{peer}: [a.rs:1] one"), format!("{peer}: two")],
+        );
+    }
+
+    #[test]
+    fn test_announce() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let name = format!("ddbg-listen-test-{}", std::process::id());
+
+        announce(&listener, &name).unwrap();
+
+        let filepath = rendezvous_filepath(&name);
+        let contents = std::fs::read_to_string(&filepath).unwrap();
+
+        std::fs::remove_file(&filepath).unwrap();
+
+        assert_eq!(contents, format!("{}\n", listener.local_addr().unwrap().port()));
+    }
+
+    #[test]
+    fn test_parse_beacon() {
+        let payload = "ddbg-beacon 1\nname=my-program\npid=1234\nsink=/tmp/log\nsink=tcp://h:1\n";
+        let beacon = parse_beacon(payload).unwrap();
+
+        assert_eq!(
+            beacon,
+            Beacon { name: "my-program", pid: 1234, sinks: vec!["/tmp/log", "tcp://h:1"] }
+        );
+        assert_eq!(
+            format_beacon(IpAddr::V4(Ipv4Addr::LOCALHOST), &beacon),
+            "127.0.0.1: my-program (pid 1234) is logging to /tmp/log, tcp://h:1",
+        );
+
+        let beacon = parse_beacon("ddbg-beacon 1\nname=idle\npid=7\nfuture=field\n").unwrap();
+
+        assert_eq!(beacon, Beacon { name: "idle", pid: 7, sinks: vec![] });
+        assert_eq!(
+            format_beacon(IpAddr::V4(Ipv4Addr::LOCALHOST), &beacon),
+            "127.0.0.1: idle (pid 7) is not logging",
+        );
+
+        assert_eq!(parse_beacon("ddbg-beacon 2\nname=x\npid=1\n"), None);
+        assert_eq!(parse_beacon("ddbg-beacon 1\nname=x\n"), None);
+        assert_eq!(parse_beacon("ddbg-beacon 1\nname=x\npid=x\n"), None);
+        assert_eq!(parse_beacon("hello"), None);
+    }
+}
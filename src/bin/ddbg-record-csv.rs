@@ -0,0 +1,184 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![cfg_attr(feature = "fatal-warnings", deny(warnings))]
+
+//! Converts a file written by `ddbg_record!()` to CSV.
+//!
+//! Usage: `ddbg-record-csv <file>`
+
+use dirty_debug::BINARY_RECORD_MAGIC;
+use std::fmt::Write as _;
+
+#[derive(Clone, Copy)]
+enum FieldType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+impl FieldType {
+    fn parse(s: &str) -> Option<FieldType> {
+        let field_type = match s {
+            "u8" => FieldType::U8,
+            "u16" => FieldType::U16,
+            "u32" => FieldType::U32,
+            "u64" => FieldType::U64,
+            "i8" => FieldType::I8,
+            "i16" => FieldType::I16,
+            "i32" => FieldType::I32,
+            "i64" => FieldType::I64,
+            "f32" => FieldType::F32,
+            "f64" => FieldType::F64,
+            _ => return None,
+        };
+
+        Some(field_type)
+    }
+
+    fn size(self) -> usize {
+        match self {
+            FieldType::U8 | FieldType::I8 => 1,
+            FieldType::U16 | FieldType::I16 => 2,
+            FieldType::U32 | FieldType::I32 | FieldType::F32 => 4,
+            FieldType::U64 | FieldType::I64 | FieldType::F64 => 8,
+        }
+    }
+
+    fn write_value(self, bytes: &[u8], out: &mut String) {
+        macro_rules! value {
+            ($type:ty) => {
+                <$type>::from_le_bytes(bytes.try_into().unwrap())
+            };
+        }
+
+        let _ = match self {
+            FieldType::U8 => write!(out, "{}", value!(u8)),
+            FieldType::U16 => write!(out, "{}", value!(u16)),
+            FieldType::U32 => write!(out, "{}", value!(u32)),
+            FieldType::U64 => write!(out, "{}", value!(u64)),
+            FieldType::I8 => write!(out, "{}", value!(i8)),
+            FieldType::I16 => write!(out, "{}", value!(i16)),
+            FieldType::I32 => write!(out, "{}", value!(i32)),
+            FieldType::I64 => write!(out, "{}", value!(i64)),
+            FieldType::F32 => write!(out, "{}", value!(f32)),
+            FieldType::F64 => write!(out, "{}", value!(f64)),
+        };
+    }
+}
+
+fn convert(data: &[u8], out: &mut impl std::io::Write) -> Result<(), String> {
+    let data =
+        data.strip_prefix(BINARY_RECORD_MAGIC).ok_or("not a dirty-debug binary record file")?;
+    let schema_end = data.iter().position(|&b| b == b'\n').ok_or("missing schema")?;
+    let schema = std::str::from_utf8(&data[..schema_end]).map_err(|_| "invalid schema")?;
+
+    let mut names: Vec<&str> = Vec::new();
+    let mut types: Vec<FieldType> = Vec::new();
+
+    for field in schema.split(',') {
+        let (name, field_type) = field.split_once(':').ok_or("invalid schema")?;
+
+        names.push(name);
+        types.push(FieldType::parse(field_type).ok_or("invalid field type")?);
+    }
+
+    let record_size: usize = types.iter().map(|t| t.size()).sum();
+    let records = &data[schema_end + 1..];
+    let mut line = String::with_capacity(256);
+
+    writeln!(out, "{}", names.join(",")).map_err(|e| e.to_string())?;
+
+    for record in records.chunks_exact(record_size) {
+        let mut offset = 0;
+
+        line.clear();
+
+        for (i, field_type) in types.iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+
+            field_type.write_value(&record[offset..offset + field_type.size()], &mut line);
+            offset += field_type.size();
+        }
+
+        writeln!(out, "{line}").map_err(|e| e.to_string())?;
+    }
+
+    if records.len() % record_size != 0 {
+        eprintln!("warning: file ends with a partial record");
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let Some(filepath) = std::env::args_os().nth(1) else {
+        eprintln!("usage: ddbg-record-csv <file>");
+        std::process::exit(1);
+    };
+
+    let result = std::fs::read(&filepath)
+        .map_err(|e| e.to_string())
+        .and_then(|data| convert(&data, &mut std::io::stdout().lock()));
+
+    if let Err(e) = result {
+        eprintln!("failed to convert \"{}\": {e}", filepath.to_string_lossy());
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use dirty_debug::{ddbg_record, impl_binary_record};
+
+    #[test]
+    fn test_convert_round_trip() {
+        struct Sample {
+            time: f64,
+            left: f32,
+            count: u8,
+            delta: i64,
+        }
+
+        impl_binary_record!(Sample { time, left, count, delta });
+
+        let filepath = std::env::temp_dir()
+            .join(format!("ddbg-record-csv-test-{}.bin", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let filepath: &'static str = Box::leak(filepath.into_boxed_str());
+
+        let _ = std::fs::remove_file(filepath);
+
+        ddbg_record!(filepath, &Sample { time: 0.5, left: -1.25, count: 7, delta: -3 });
+        ddbg_record!(filepath, &Sample { time: 1.0, left: 2.0, count: 255, delta: i64::MAX });
+
+        let data = std::fs::read(filepath).unwrap();
+        let mut out: Vec<u8> = Vec::new();
+
+        std::fs::remove_file(filepath).unwrap();
+        convert(&data, &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "time,left,count,delta\n0.5,-1.25,7,-3\n1,2,255,9223372036854775807\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_not_a_record_file() {
+        assert!(convert(b"hello\n", &mut Vec::new()).is_err());
+    }
+}
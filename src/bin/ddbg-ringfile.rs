@@ -0,0 +1,125 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![cfg_attr(feature = "fatal-warnings", deny(warnings))]
+
+//! Prints the messages of a file written by a `ringfile://` uri, from oldest to newest.
+//!
+//! Usage: `ddbg-ringfile <file>`
+
+use std::io::Write;
+
+const MAGIC: &[u8] = b"DDBGRNG1";
+const HEADER_SIZE: usize = 32;
+
+/// Returns the contents of the ring file in the order they were written.  If the buffer wrapped
+/// around, the oldest line is incomplete and is dropped.
+fn linearize(data: &[u8]) -> Result<Vec<u8>, String> {
+    let field = |offset: usize| -> Option<u64> {
+        Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+    };
+
+    let (Some(capacity), Some(written)) = (field(8), field(16)) else {
+        return Err("file too short".to_owned());
+    };
+
+    if &data[0..MAGIC.len()] != MAGIC {
+        return Err("not a ring file".to_owned());
+    }
+
+    let ring = &data[HEADER_SIZE.min(data.len())..];
+
+    if capacity == 0 || ring.len() as u64 != capacity {
+        return Err("ring file is truncated".to_owned());
+    }
+
+    let Ok(start) = usize::try_from(written % capacity) else {
+        return Err("invalid ring file".to_owned());
+    };
+
+    if written <= capacity {
+        return Ok(ring[..usize::try_from(written).unwrap_or(0)].to_vec());
+    }
+
+    let mut linear = ring[start..].to_vec();
+
+    linear.extend_from_slice(&ring[..start]);
+
+    let skip = linear.iter().position(|&b| b == b'\n').map_or(linear.len(), |i| i + 1);
+
+    Ok(linear.split_off(skip))
+}
+
+fn main() {
+    let Some(filepath) = std::env::args_os().nth(1) else {
+        eprintln!("usage: ddbg-ringfile <file>");
+        std::process::exit(1);
+    };
+
+    let result = std::fs::read(&filepath)
+        .map_err(|e| e.to_string())
+        .and_then(|data| linearize(&data))
+        .and_then(|linear| std::io::stdout().lock().write_all(&linear).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        eprintln!("failed to read \"{}\": {e}", filepath.to_string_lossy());
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use dirty_debug::ddbg;
+
+    #[test]
+    fn test_linearize() {
+        let filepath = std::env::temp_dir()
+            .join(format!("ddbg-ringfile-test-{}", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let uri: &'static str =
+            Box::leak(format!("ringfile://{filepath}?size=256").into_boxed_str());
+        let messages = |linear: Vec<u8>| -> Vec<String> {
+            String::from_utf8(linear)
+                .unwrap()
+                .lines()
+                .map(|line| line.split_once("] ").unwrap().1.to_owned())
+                .collect()
+        };
+
+        let _ = std::fs::remove_file(&filepath);
+
+        ddbg!(uri, "message 0");
+
+        let before_wrapping = messages(linearize(&std::fs::read(&filepath).unwrap()).unwrap());
+
+        for i in 1..30 {
+            ddbg!(uri, "message {i}");
+        }
+
+        let data = std::fs::read(&filepath).unwrap();
+
+        std::fs::remove_file(&filepath).unwrap();
+
+        let after_wrapping = messages(linearize(&data).unwrap());
+
+        assert!(before_wrapping[0].starts_with("opening \"ringfile://"), "{before_wrapping:?}");
+        assert_eq!(before_wrapping[1..], ["message 0"]);
+
+        // Only the newest lines fit, and the oldest of them, which was partly overwritten, is
+        // dropped.
+        assert_eq!(data.len(), HEADER_SIZE + 256);
+        assert!(after_wrapping.len() < 30);
+        assert_eq!(
+            after_wrapping,
+            (30 - after_wrapping.len()..30).map(|i| format!("message {i}")).collect::<Vec<_>>(),
+        );
+
+        assert_eq!(linearize(&data[..20]), Err("file too short".to_owned()));
+        assert_eq!(linearize(&data[..100]), Err("ring file is truncated".to_owned()));
+        assert_eq!(linearize(&[0; 64]), Err("not a ring file".to_owned()));
+    }
+}
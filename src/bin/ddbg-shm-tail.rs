@@ -0,0 +1,267 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![cfg_attr(feature = "fatal-warnings", deny(warnings))]
+
+//! Follows the output written to a `shm://` uri, printing new messages as they are written.
+//!
+//! Usage: `ddbg-shm-tail <name>`
+
+#[cfg(all(unix, not(target_os = "android")))]
+mod shm {
+    use std::ffi::CString;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+
+    const MAGIC: &[u8; 8] = b"DDBGSHM1";
+    const HEADER_SIZE: usize = 64;
+    const CAPACITY_OFFSET: usize = 8;
+    const WRITTEN_OFFSET: usize = 16;
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    struct Ring {
+        memory: *const u8,
+        capacity: u64,
+    }
+
+    impl Ring {
+        fn open(name: &str) -> Result<Ring, String> {
+            let c_name = CString::new(format!("/{name}")).map_err(|e| e.to_string())?;
+
+            // SAFETY: `c_name` is a valid C string, and `stat` is only read if `fstat()`
+            // succeeds.  The file descriptor is not needed after the mapping is created.
+            let (memory, len) = unsafe {
+                let fd = libc::shm_open(c_name.as_ptr(), libc::O_RDONLY, 0);
+
+                if fd == -1 {
+                    return Err(std::io::Error::last_os_error().to_string());
+                }
+
+                let mut stat: libc::stat = std::mem::zeroed();
+                let len = match libc::fstat(fd, &mut stat) {
+                    0 => usize::try_from(stat.st_size).unwrap_or(0),
+                    _ => 0,
+                };
+                let memory = match len >= HEADER_SIZE {
+                    true => libc::mmap(
+                        std::ptr::null_mut(),
+                        len,
+                        libc::PROT_READ,
+                        libc::MAP_SHARED,
+                        fd,
+                        0,
+                    ),
+                    false => libc::MAP_FAILED,
+                };
+
+                libc::close(fd);
+
+                if memory == libc::MAP_FAILED {
+                    return Err("not a dirty-debug shared memory ring buffer".to_owned());
+                }
+
+                (memory.cast::<u8>().cast_const(), len)
+            };
+
+            // SAFETY: The header is inside the mapping.
+            let (magic, capacity) = unsafe {
+                (
+                    std::slice::from_raw_parts(memory, MAGIC.len()),
+                    u64::from_ne_bytes(
+                        std::slice::from_raw_parts(memory.add(CAPACITY_OFFSET), 8)
+                            .try_into()
+                            .unwrap_or_default(),
+                    ),
+                )
+            };
+
+            match magic == MAGIC && capacity > 0 && capacity == (len - HEADER_SIZE) as u64 {
+                true => Ok(Ring { memory, capacity }),
+                false => Err("not a dirty-debug shared memory ring buffer".to_owned()),
+            }
+        }
+
+        #[allow(clippy::cast_ptr_alignment)]
+        fn written(&self) -> u64 {
+            // SAFETY: The header is inside the mapping, and `WRITTEN_OFFSET` is aligned since the
+            // mapping is page-aligned.
+            let written = unsafe { &*self.memory.add(WRITTEN_OFFSET).cast::<AtomicU64>() };
+
+            written.load(Ordering::Acquire)
+        }
+
+        /// Copies the bytes of the stream in `start..end`, which must be at most `capacity` bytes.
+        fn copy(&self, start: u64, end: u64, out: &mut Vec<u8>) {
+            out.clear();
+
+            for position in start..end {
+                let index = usize::try_from(position % self.capacity).unwrap_or(0);
+
+                // SAFETY: The index is within the data area.
+                out.push(unsafe { self.memory.add(HEADER_SIZE + index).read_volatile() });
+            }
+        }
+    }
+
+    /// Writes to `out` what was written to the ring since `position`, and moves `position` past
+    /// it.  Returns whether there was anything new.
+    fn read_new(
+        ring: &Ring,
+        position: &mut u64,
+        buffer: &mut Vec<u8>,
+        out: &mut impl Write,
+    ) -> std::io::Result<bool> {
+        let written = ring.written();
+
+        if written < *position {
+            // The writer started over.
+            *position = 0;
+        }
+
+        if written == *position {
+            return Ok(false);
+        }
+
+        let mut start = (*position).max(written.saturating_sub(ring.capacity));
+
+        ring.copy(start, written, buffer);
+
+        // The writer may have overwritten what we copied in the meantime.
+        let overwritten = ring.written().saturating_sub(ring.capacity).saturating_sub(start);
+        let overwritten = usize::try_from(overwritten).unwrap_or(usize::MAX).min(buffer.len());
+        let mut data = &buffer[overwritten..];
+
+        start += overwritten as u64;
+
+        if start > *position {
+            // Skip the rest of the line we lost the beginning of.
+            let skip = data.iter().position(|&b| b == b'\n').map_or(data.len(), |i| i + 1);
+
+            data = &data[skip..];
+            writeln!(out, "[ddbg-shm-tail] {} bytes lost", start - *position + skip as u64)?;
+        }
+
+        out.write_all(data)?;
+        out.flush()?;
+        *position = written;
+
+        Ok(true)
+    }
+
+    pub(crate) fn tail(name: &str) -> Result<(), String> {
+        let ring = Ring::open(name)?;
+        let mut stdout = std::io::stdout().lock();
+        let mut position = ring.written();
+        let mut buffer = Vec::new();
+
+        loop {
+            if !read_new(&ring, &mut position, &mut buffer, &mut stdout)
+                .map_err(|e| e.to_string())?
+            {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use dirty_debug::ddbg;
+
+        #[test]
+        fn test_read_new() {
+            let name = format!("dirty-debug-shm-tail-test-{}", std::process::id());
+            let uri: &'static str = Box::leak(format!("shm://{name}?size=256").into_boxed_str());
+            let mut buffer: Vec<u8> = Vec::new();
+            let mut read = |ring: &Ring, position: &mut u64| {
+                let mut out: Vec<u8> = Vec::new();
+
+                read_new(ring, position, &mut buffer, &mut out).unwrap();
+                String::from_utf8(out).unwrap()
+            };
+            let messages = |log: &str| -> Vec<String> {
+                log.lines().map(|line| line.split_once("] ").unwrap().1.to_owned()).collect()
+            };
+
+            ddbg!(uri, "first");
+
+            let ring = Ring::open(&name).unwrap();
+            let mut position: u64 = 0;
+
+            let log = read(&ring, &mut position);
+
+            assert!(log.starts_with("[dirty-debug] opening \""), "{log}");
+            assert_eq!(messages(&log)[1..], ["first"]);
+            assert_eq!(position, ring.written());
+            assert_eq!(read(&ring, &mut position), "");
+
+            // These wrap around the end of the data area.
+            for i in 0..3 {
+                ddbg!(uri, "message {i} of the second batch");
+            }
+
+            let log = read(&ring, &mut position);
+
+            assert!(position > ring.capacity);
+            assert_eq!(
+                messages(&log),
+                (0..3).map(|i| format!("message {i} of the second batch")).collect::<Vec<_>>(),
+            );
+
+            // The reader falls behind: the writer goes around more than once before it reads.
+            let behind = position;
+
+            for i in 0..20 {
+                ddbg!(uri, "message {i} of the third batch");
+            }
+
+            let log = read(&ring, &mut position);
+            let (lost, rest) = log.split_once('\n').unwrap();
+            let lost: u64 = lost
+                .strip_prefix("[ddbg-shm-tail] ")
+                .and_then(|lost| lost.strip_suffix(" bytes lost"))
+                .unwrap()
+                .parse()
+                .unwrap();
+
+            // Every byte written since we last read is either lost or shown.
+            assert_eq!(lost + rest.len() as u64, ring.written() - behind);
+
+            let rest = messages(rest);
+
+            assert!(rest.len() < 20);
+            assert_eq!(
+                rest,
+                (20 - rest.len()..20)
+                    .map(|i| format!("message {i} of the third batch"))
+                    .collect::<Vec<_>>(),
+            );
+
+            let c_name = CString::new(format!("/{name}")).unwrap();
+
+            // SAFETY: `c_name` is a valid C string.
+            unsafe { libc::shm_unlink(c_name.as_ptr()) };
+        }
+    }
+}
+
+fn main() {
+    let Some(name) = std::env::args().nth(1) else {
+        eprintln!("usage: ddbg-shm-tail <name>");
+        std::process::exit(1);
+    };
+
+    #[cfg(all(unix, not(target_os = "android")))]
+    let result = shm::tail(&name);
+    #[cfg(not(all(unix, not(target_os = "android"))))]
+    let result: Result<(), String> =
+        Err("shared memory is only supported on unix (other than android)".to_owned());
+
+    if let Err(e) = result {
+        eprintln!("failed to tail \"{name}\": {e}");
+        std::process::exit(1);
+    }
+}
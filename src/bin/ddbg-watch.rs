@@ -0,0 +1,250 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#![cfg_attr(feature = "fatal-warnings", deny(warnings))]
+
+//! Tails all the files of a `merge://` directory, merging the lines of every process by their
+//! timestamp.
+//!
+//! If the programs log with `DIRTY_DEBUG_BANNER=true`, this warns when a program is now running a
+//! different build than before.
+//!
+//! Usage: `ddbg-watch <dir>`
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A line of a merge file, together with its continuation lines.
+struct Entry {
+    timestamp: (u64, u32),
+    source: String,
+    text: String,
+}
+
+/// The state of a file we are tailing.
+#[derive(Default)]
+struct Tail {
+    offset: u64,
+    /// Data after the last newline, which we only process when the line is complete.
+    partial: Vec<u8>,
+}
+
+/// Extracts the program name and build id of a session banner.
+fn parse_banner(text: &str) -> Option<(&str, &str)> {
+    let fields =
+        dirty_debug::dirty_skip_prefix(text).strip_prefix("[dirty-debug] session started: ")?;
+    let field = |name: &str| {
+        fields.split(' ').find_map(|field| field.strip_prefix(name)?.strip_prefix('='))
+    };
+
+    Some((field("name")?, field("build")?))
+}
+
+/// Records the build of the program of a session banner, returning a warning if it differs from
+/// the build that program ran before.
+fn check_build(builds: &mut HashMap<String, String>, text: &str) -> Option<String> {
+    let (name, build) = parse_banner(text)?;
+    let previous = builds.insert(name.to_owned(), build.to_owned())?;
+
+    (previous != build)
+        .then(|| format!("{name} is running a different build than before ({previous} → {build})"))
+}
+
+fn parse_timestamp(s: &str) -> Option<(u64, u32)> {
+    let (secs, nanos) = s.split_once('.')?;
+
+    Some((secs.parse().ok()?, nanos.parse().ok()?))
+}
+
+fn read_new_lines(path: &Path, tail: &mut Tail) -> std::io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+
+    file.seek(SeekFrom::Start(tail.offset))?;
+
+    let read = file.read_to_end(&mut tail.partial)?;
+
+    tail.offset += read as u64;
+
+    let Some(last_newline) = tail.partial.iter().rposition(|&b| b == b'\n') else {
+        return Ok(Vec::new());
+    };
+
+    let rest = tail.partial.split_off(last_newline + 1);
+    let complete = std::mem::replace(&mut tail.partial, rest);
+
+    Ok(String::from_utf8_lossy(&complete).lines().map(str::to_owned).collect())
+}
+
+fn poll(dir: &Path, tails: &mut HashMap<PathBuf, Tail>) -> std::io::Result<Vec<Entry>> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for dir_entry in std::fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+
+        if path.extension() != Some(OsStr::new("log")) {
+            continue;
+        }
+
+        let source = path.file_stem().map_or(String::new(), |s| s.to_string_lossy().into_owned());
+        let tail = tails.entry(path.clone()).or_default();
+
+        for line in read_new_lines(&path, tail)? {
+            let parsed = line
+                .split_once(' ')
+                .and_then(|(timestamp, text)| Some((parse_timestamp(timestamp)?, text)));
+
+            match (parsed, entries.last_mut()) {
+                (Some((timestamp, text)), _) => {
+                    entries.push(Entry {
+                        timestamp,
+                        source: source.clone(),
+                        text: text.to_owned(),
+                    });
+                }
+                // A line without a timestamp is the continuation of a multiline message.
+                (None, Some(last)) if last.source == source => {
+                    last.text.push('\n');
+                    last.text.push_str(&line);
+                }
+                (None, _) => {
+                    entries.push(Entry { timestamp: (0, 0), source: source.clone(), text: line });
+                }
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| entry.timestamp);
+
+    Ok(entries)
+}
+
+fn main() {
+    let Some(dir) = std::env::args_os().nth(1).map(PathBuf::from) else {
+        eprintln!("usage: ddbg-watch <dir>");
+        std::process::exit(1);
+    };
+
+    let mut tails: HashMap<PathBuf, Tail> = HashMap::new();
+    let mut builds: HashMap<String, String> = HashMap::new();
+
+    loop {
+        match poll(&dir, &mut tails) {
+            Ok(entries) => {
+                for entry in entries {
+                    if let Some(warning) = check_build(&mut builds, &entry.text) {
+                        eprintln!("ddbg-watch: warning: {warning}");
+                    }
+
+                    println!("{}: {}", entry.source, entry.text);
+                }
+            }
+            Err(e) => {
+                eprintln!("failed to read \"{}\": {e}", dir.display());
+                std::process::exit(1);
+            }
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1700000000.000000042"), Some((1_700_000_000, 42)));
+        assert_eq!(parse_timestamp("0.999999999"), Some((0, 999_999_999)));
+        assert_eq!(parse_timestamp("1700000000"), None);
+        assert_eq!(parse_timestamp("17x.000000042"), None);
+        assert_eq!(parse_timestamp("[src/main.rs:3]"), None);
+    }
+
+    #[test]
+    fn test_parse_banner() {
+        assert_eq!(
+            parse_banner("[dirty-debug] session started: name=server pid=42 build=abc123"),
+            Some(("server", "abc123")),
+        );
+        assert_eq!(
+            parse_banner(
+                "2026-10-16T12:34:56.123456Z [server pid=42] [dirty-debug] session started: \
+                 name=server pid=42 build=abc123"
+            ),
+            Some(("server", "abc123")),
+        );
+        assert_eq!(parse_banner("[dirty-debug] session started: pid=42 build=abc123"), None);
+        assert_eq!(parse_banner("[src/main.rs:3] session started: name=x build=y"), None);
+    }
+
+    #[test]
+    fn test_check_build() {
+        let mut builds: HashMap<String, String> = HashMap::new();
+        let banner =
+            |build: &str| format!("[dirty-debug] session started: name=server build={build}");
+
+        assert_eq!(check_build(&mut builds, &banner("abc")), None);
+        assert_eq!(check_build(&mut builds, &banner("abc")), None);
+        assert_eq!(check_build(&mut builds, "[src/main.rs:3] hello"), None);
+        assert_eq!(
+            check_build(&mut builds, &banner("def")).as_deref(),
+            Some("server is running a different build than before (abc → def)"),
+        );
+        assert_eq!(check_build(&mut builds, &banner("def")), None);
+    }
+
+    #[test]
+    fn test_poll() {
+        let dir = std::env::temp_dir().join(format!("ddbg-watch-test-{}", std::process::id()));
+        let append = |name: &str, data: &str| {
+            let mut file = File::options().create(true).append(true).open(dir.join(name)).unwrap();
+
+            file.write_all(data.as_bytes()).unwrap();
+        };
+        let texts = |entries: Vec<Entry>| -> Vec<(String, String)> {
+            entries.into_iter().map(|entry| (entry.source, entry.text)).collect()
+        };
+        let mut tails: HashMap<PathBuf, Tail> = HashMap::new();
+
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+
+        append("a.1.log", "10.000000000 [a.rs:1] a first\n30.000000000 [a.rs:2] two\nlines\n");
+        append("b.2.log", "20.000000000 [b.rs:1] b first\n40.000000000 [b.rs:2] b par");
+        append("notes.txt", "not a merge file\n");
+
+        let first = texts(poll(&dir, &mut tails).unwrap());
+
+        // The continuation of a message that was in a previous batch has no timestamp.
+        append("a.1.log", "more lines\n");
+        append("b.2.log", "tial\n");
+
+        let second = texts(poll(&dir, &mut tails).unwrap());
+        let third = texts(poll(&dir, &mut tails).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let entry = |source: &str, text: &str| (source.to_owned(), text.to_owned());
+
+        assert_eq!(
+            first,
+            [
+                entry("a.1", "[a.rs:1] a first"),
+                entry("b.2", "[b.rs:1] b first"),
+                entry("a.1", "[a.rs:2] two\nlines"),
+            ],
+        );
+        assert_eq!(second, [entry("a.1", "more lines"), entry("b.2", "[b.rs:2] b partial")]);
+        assert!(third.is_empty());
+    }
+}
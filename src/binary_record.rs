@@ -0,0 +1,278 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::append_entry;
+use dashmap::DashMap;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::LazyLock;
+
+/// The first line of a binary record file.  This is shared with `ddbg-record-csv`.
+#[doc(hidden)]
+pub const BINARY_RECORD_MAGIC: &[u8] = b"DDBGREC1\n";
+
+/// Files we are writing binary records to, indexed by their path.
+static DIRTY_RECORD_FILES: LazyLock<DashMap<&str, RecordFile>> = LazyLock::new(DashMap::new);
+
+/// Writes a binary record to the given file.  This is meant for very high-rate numeric telemetry,
+/// where formatting text would be way too slow.
+///
+/// The file starts with a header describing the fields of the record (written once, when the file
+/// is created), followed by every record with a fixed layout.  Writing a record with different
+/// fields to an existing file (e.g. after changing the record type and running the program again)
+/// is an error.  The `ddbg-record-csv` tool converts these files to CSV.
+///
+/// The record type must implement [`BinaryRecord`], which is easily done with
+/// [`impl_binary_record!()`](crate::impl_binary_record).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::{ddbg_record, impl_binary_record};
+/// #
+/// struct Sample {
+///     time: f64,
+///     left: f32,
+///     right: f32,
+/// }
+///
+/// impl_binary_record!(Sample { time, left, right });
+///
+/// # let samples: Vec<Sample> = Vec::new();
+/// for sample in &samples {
+///     ddbg_record!("/tmp/samples.bin", sample);
+/// }
+/// ```
+#[macro_export]
+macro_rules! ddbg_record {
+    ($uri:expr, $record:expr $(,)?) => {{
+        $crate::dirty_log_binary_record($uri, $record);
+    }};
+}
+
+/// Implements [`BinaryRecord`] for a struct with numeric fields.  The fields will be written in
+/// the order they are listed.
+///
+/// See [`ddbg_record!()`](crate::ddbg_record) for an example.
+#[macro_export]
+macro_rules! impl_binary_record {
+    ($type:ty { $($field:ident),+ $(,)? }) => {
+        impl $crate::BinaryRecord for $type {
+            fn visit_fields(&self, visit: &mut dyn FnMut(&'static str, $crate::BinaryValue)) {
+                $(
+                    visit(::std::stringify!($field), $crate::BinaryValue::from(self.$field));
+                )+
+            }
+        }
+    };
+}
+
+/// A type that can be logged with [`ddbg_record!()`](crate::ddbg_record).
+pub trait BinaryRecord {
+    /// Calls `visit` with the name and value of every field.  The fields must always be visited in
+    /// the same order and with the same types.
+    fn visit_fields(&self, visit: &mut dyn FnMut(&'static str, BinaryValue));
+}
+
+/// The value of a field of a [`BinaryRecord`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BinaryValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+macro_rules! impl_binary_value_from {
+    ($($type:ty => $variant:ident),+) => {
+        $(
+            impl From<$type> for BinaryValue {
+                fn from(value: $type) -> BinaryValue {
+                    BinaryValue::$variant(value)
+                }
+            }
+        )+
+    };
+}
+
+impl_binary_value_from!(
+    u8 => U8, u16 => U16, u32 => U32, u64 => U64,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64,
+    f32 => F32, f64 => F64
+);
+
+impl BinaryValue {
+    fn type_name(self) -> &'static str {
+        match self {
+            BinaryValue::U8(_) => "u8",
+            BinaryValue::U16(_) => "u16",
+            BinaryValue::U32(_) => "u32",
+            BinaryValue::U64(_) => "u64",
+            BinaryValue::I8(_) => "i8",
+            BinaryValue::I16(_) => "i16",
+            BinaryValue::I32(_) => "i32",
+            BinaryValue::I64(_) => "i64",
+            BinaryValue::F32(_) => "f32",
+            BinaryValue::F64(_) => "f64",
+        }
+    }
+
+    fn write_le_bytes(self, buffer: &mut Vec<u8>) {
+        match self {
+            BinaryValue::U8(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::U16(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::U32(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::U64(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::I8(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::I16(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::I32(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::I64(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::F32(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+            BinaryValue::F64(v) => buffer.extend_from_slice(&v.to_le_bytes()),
+        }
+    }
+}
+
+/// Returns the schema of the record, e.g. `time:f64,left:f32,right:f32`.
+fn schema(record: &(impl BinaryRecord + ?Sized)) -> String {
+    let mut schema = String::with_capacity(64);
+
+    record.visit_fields(&mut |name, value| {
+        if !schema.is_empty() {
+            schema.push(',');
+        }
+
+        schema.push_str(name);
+        schema.push(':');
+        schema.push_str(value.type_name());
+    });
+
+    schema
+}
+
+/// Strips the field `name:type_name` from the start of `schema`, along with the comma after it.
+fn strip_field<'a>(schema: &'a str, name: &str, type_name: &str) -> Option<&'a str> {
+    let rest = schema.strip_prefix(name)?.strip_prefix(':')?.strip_prefix(type_name)?;
+
+    match rest.strip_prefix(',') {
+        Some(rest) => Some(rest),
+        None => rest.is_empty().then_some(rest),
+    }
+}
+
+/// Reads the schema from the header of an existing binary record file.
+fn read_schema(file: &File) -> io::Result<String> {
+    let mut reader = BufReader::new(file);
+    let mut magic = [0; BINARY_RECORD_MAGIC.len()];
+
+    reader.read_exact(&mut magic)?;
+
+    if magic != BINARY_RECORD_MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a binary record file"));
+    }
+
+    let mut schema = String::new();
+
+    reader.read_line(&mut schema)?;
+
+    match schema.strip_suffix('\n') {
+        Some(schema) => Ok(schema.to_owned()),
+        None => Err(io::Error::new(io::ErrorKind::InvalidData, "truncated binary record header")),
+    }
+}
+
+struct RecordFile {
+    file: File,
+    schema: String,
+    buffer: Vec<u8>,
+}
+
+impl RecordFile {
+    fn open(filepath: &str, schema: String) -> io::Result<RecordFile> {
+        let mut file = File::options().create(true).read(true).append(true).open(filepath)?;
+
+        if file.metadata()?.len() == 0 {
+            file.write_all(BINARY_RECORD_MAGIC)?;
+            file.write_all(schema.as_bytes())?;
+            file.write_all(b"\n")?;
+        } else {
+            let existing_schema = read_schema(&file)?;
+
+            if existing_schema != schema {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("the file has schema \"{existing_schema}\", not \"{schema}\""),
+                ));
+            }
+        }
+
+        Ok(RecordFile { file, schema, buffer: Vec::with_capacity(64) })
+    }
+
+    fn write(&mut self, record: &(impl BinaryRecord + ?Sized)) -> io::Result<()> {
+        let buffer = &mut self.buffer;
+        // We check the fields against the schema as we go, which is much cheaper than building the
+        // schema of every record.
+        let mut schema_left: Option<&str> = Some(&self.schema);
+
+        buffer.clear();
+        record.visit_fields(&mut |name, value| {
+            value.write_le_bytes(buffer);
+            schema_left = schema_left.and_then(|s| strip_field(s, name, value.type_name()));
+        });
+
+        if schema_left != Some("") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "record does not match the schema of the file",
+            ));
+        }
+
+        append_entry(&mut self.file, buffer)
+    }
+}
+
+fn write_binary_record(
+    filepath: &'static str,
+    record: &(impl BinaryRecord + ?Sized),
+) -> io::Result<()> {
+    let mut entry = DIRTY_RECORD_FILES
+        .entry(filepath)
+        .or_try_insert_with(|| RecordFile::open(filepath, schema(record)))?;
+
+    entry.value_mut().write(record)
+}
+
+/// Logs the given binary record.
+#[doc(hidden)]
+pub fn dirty_log_binary_record(uri: &'static str, record: &(impl BinaryRecord + ?Sized)) {
+    let filepath = uri.strip_prefix("file://").unwrap_or(uri);
+
+    if let Err(e) = write_binary_record(filepath, record) {
+        crate::report_failure(uri, &e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_strip_field() {
+        assert_eq!(strip_field("a:u8,b:f32", "a", "u8"), Some("b:f32"));
+        assert_eq!(strip_field("b:f32", "b", "f32"), Some(""));
+        assert_eq!(strip_field("a:u16,b:f32", "a", "u1"), None);
+        assert_eq!(strip_field("ab:u8", "a", "u8"), None);
+        assert_eq!(strip_field("a:u8", "a", "i8"), None);
+        assert_eq!(strip_field("", "a", "u8"), None);
+    }
+}
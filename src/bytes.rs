@@ -0,0 +1,113 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The next number to try for `{n}` in the file path of a `ddbg_bytes!()` or `ddbg_dot!()`.
+static DIRTY_DUMP_NUMBER: AtomicU64 = AtomicU64::new(0);
+
+/// Writes raw bytes to a file of their own, and logs its path, the length, and a hash of the
+/// bytes.  This is better than logging a large binary payload as text.
+///
+/// The `{n}` in the file path is replaced by a number that makes it a new file, so every dump is
+/// kept (files left over from previous runs are not overwritten).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_bytes;
+/// # let buffer: Vec<u8> = vec![0xca, 0xfe];
+/// #
+/// ddbg_bytes!("/tmp/log", "/tmp/dump_{n}.bin", &buffer);
+/// ```
+///
+/// This logs lines like `&buffer: 2 bytes dumped to "/tmp/dump_0.bin" (fnv-1a 0x…)`.
+#[macro_export]
+macro_rules! ddbg_bytes {
+    ($uri:expr, $filepath:expr, $bytes:expr $(,)?) => {{
+        $crate::dirty_log_bytes(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($bytes),
+            ::std::convert::AsRef::<str>::as_ref(&$filepath),
+            ::std::convert::AsRef::<[u8]>::as_ref(&$bytes),
+        );
+    }};
+}
+
+/// FNV-1a, 64 bits.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Creates the dump file, replacing `{n}` in the template by the first number that gives a path
+/// that does not exist yet.
+pub(crate) fn create_dump_file(filepath_template: &str) -> io::Result<(File, String)> {
+    if !filepath_template.contains("{n}") {
+        return Ok((File::create(filepath_template)?, filepath_template.to_owned()));
+    }
+
+    loop {
+        let n = DIRTY_DUMP_NUMBER.fetch_add(1, Ordering::Relaxed);
+        let filepath = filepath_template.replace("{n}", &n.to_string());
+
+        match OpenOptions::new().write(true).create_new(true).open(&filepath) {
+            Ok(file) => return Ok((file, filepath)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => (),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Logs a `ddbg_bytes!()`.
+#[doc(hidden)]
+pub fn dirty_log_bytes(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    filepath_template: &str,
+    bytes: &[u8],
+) {
+    let len = bytes.len();
+    let result = create_dump_file(filepath_template)
+        .and_then(|(mut dump, filepath)| dump.write_all(bytes).map(|()| filepath));
+
+    match result {
+        Ok(filepath) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!(
+                "{label}: {len} bytes dumped to \"{filepath}\" (fnv-1a {:#018x})",
+                fnv1a(bytes),
+            ),
+        ),
+        Err(e) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label}: cannot dump {len} bytes to \"{filepath_template}\": {e}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
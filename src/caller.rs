@@ -0,0 +1,45 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::panic::Location;
+
+/// Like [`ddbg!()`](crate::ddbg), but when used in a function marked with `#[track_caller]` the
+/// message is prefixed with the location of the caller of that function, instead of the location
+/// of the macro.  Useful in helper functions, so that their messages point to where they are
+/// called from.
+///
+/// Functions marked with `#[track_caller]` pass the location of their own caller along, so this
+/// works across several levels of helpers.  Without `#[track_caller]` this is just like `ddbg!()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_caller;
+/// #
+/// #[track_caller]
+/// fn checked_div(a: u32, b: u32) -> Option<u32> {
+///     if b == 0 {
+///         ddbg_caller!("/tmp/log", "division of {} by zero", a);
+///     }
+///
+///     a.checked_div(b)
+/// }
+/// ```
+#[macro_export]
+macro_rules! ddbg_caller {
+    ($uri:expr, $($arg:tt)+) => {{
+        $crate::dirty_log_caller($uri, ::std::format_args!($($arg)+));
+    }};
+}
+
+/// Logs a `ddbg_caller!()`.
+#[doc(hidden)]
+#[track_caller]
+pub fn dirty_log_caller(uri: &'static str, args: fmt::Arguments<'_>) {
+    let location = Location::caller();
+
+    crate::dirty_log_message(uri, location.file(), location.line(), args);
+}
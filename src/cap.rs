@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! The cap on the total output of the process (`DIRTY_DEBUG_MAX_OUTPUT`).
+
+use crate::config;
+use crate::record::Location;
+use dashmap::{DashMap, DashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::LazyLock;
+
+static DIRTY_OUTPUT_CAP: LazyLock<Option<OutputCap>> =
+    LazyLock::new(|| config::max_output().map(OutputCap::new));
+
+struct OutputCap {
+    max: u64,
+    written: AtomicU64,
+    /// Whether the cap was exceeded.  From then on nothing is logged, not even messages that would
+    /// still fit.
+    reached: AtomicBool,
+    /// How many messages were not logged, per destination and call site.
+    suppressed: DashMap<(&'static str, &'static str, u32), u64>,
+    /// The destinations we already told that the cap was reached.
+    notified: DashSet<&'static str>,
+}
+
+impl OutputCap {
+    fn new(max: u64) -> OutputCap {
+        OutputCap {
+            max,
+            written: AtomicU64::new(0),
+            reached: AtomicBool::new(false),
+            suppressed: DashMap::new(),
+            notified: DashSet::new(),
+        }
+    }
+
+    /// Accounts for a message of `len` bytes and returns whether it should be logged.
+    fn admit(&self, uri: &'static str, location: Location, len: usize) -> bool {
+        if !self.reached.load(Ordering::Relaxed) {
+            let len = u64::try_from(len).unwrap_or(u64::MAX);
+            let written = self
+                .written
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |w| Some(w.saturating_add(len)))
+                .unwrap_or_else(|w| w)
+                .saturating_add(len);
+
+            if written <= self.max {
+                return true;
+            }
+
+            self.reached.store(true, Ordering::Relaxed);
+        }
+
+        *self.suppressed.entry((uri, location.file, location.line)).or_default() += 1;
+
+        if self.notified.insert(uri) {
+            crate::report::report_on_exit();
+
+            let message =
+                format!("output cap of {} bytes reached: messages are no longer logged", self.max);
+
+            eprintln!("dirty-debug: warning: {message}");
+            crate::dirty_log_meta(uri, format_args!("{message}"));
+        }
+
+        false
+    }
+
+    fn summary(&self) -> Vec<(&'static str, &'static str, u32, u64)> {
+        let mut summary: Vec<(&'static str, &'static str, u32, u64)> = self
+            .suppressed
+            .iter()
+            .map(|entry| {
+                let (uri, file, line) = *entry.key();
+
+                (uri, file, line, *entry.value())
+            })
+            .collect();
+
+        summary.sort_unstable();
+        summary
+    }
+}
+
+/// Returns whether a message of `len` bytes should be logged, i.e. if it does not go over the cap.
+pub(crate) fn admit(uri: &'static str, location: Location, len: usize) -> bool {
+    DIRTY_OUTPUT_CAP.as_ref().map_or(true, |cap| cap.admit(uri, location, len))
+}
+
+pub(crate) fn report() {
+    let Some(cap) = DIRTY_OUTPUT_CAP.as_ref() else {
+        return;
+    };
+
+    for (uri, file, line, count) in cap.summary() {
+        crate::dirty_log_meta(
+            uri,
+            format_args!("output cap: {count} messages from {file}:{line} not logged"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_output_cap() {
+        let cap = OutputCap::new(10);
+        let location = |line: u32| Location { file: "src/main.rs", line };
+
+        assert!(cap.admit("null://", location(1), 6));
+        assert!(!cap.admit("null://", location(1), 100));
+        // The cap was reached, so even messages that would fit are not logged anymore.
+        assert!(!cap.admit("null://", location(2), 4));
+        assert!(!cap.admit("null://", location(3), 1));
+        assert!(!cap.admit("null://", location(3), 0));
+
+        assert_eq!(
+            cap.summary(),
+            [
+                ("null://", "src/main.rs", 1, 1),
+                ("null://", "src/main.rs", 2, 1),
+                ("null://", "src/main.rs", 3, 2),
+            ],
+        );
+    }
+}
@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+/// Logs the value of an expression, but only when it differs from the previous time this call site
+/// was executed, showing the old and the new value.  This makes it cheap to track a state machine
+/// that is polled in a hot loop.
+///
+/// The values are compared by their [`Debug`](std::fmt::Debug) representation.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_changed;
+/// # let connection_state = "Connecting";
+/// #
+/// loop {
+///     ddbg_changed!("/tmp/log", connection_state);
+/// #   break;
+/// }
+/// ```
+///
+/// This logs `connection_state = "Connecting"` the first time, and then lines like
+/// `connection_state: "Connecting" → "Connected"`.
+#[macro_export]
+macro_rules! ddbg_changed {
+    ($uri:expr, $val:expr $(,)?) => {{
+        static CHANGED: $crate::Changed = $crate::Changed::new();
+
+        CHANGED.log($uri, ::std::file!(), ::std::line!(), ::std::stringify!($val), &$val);
+    }};
+}
+
+/// The state of a [`ddbg_changed!()`](crate::ddbg_changed) call site.
+#[doc(hidden)]
+pub struct Changed {
+    previous: Mutex<Option<String>>,
+}
+
+impl Changed {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Changed {
+        Changed { previous: Mutex::new(None) }
+    }
+
+    pub fn log<T: fmt::Debug + ?Sized>(
+        &self,
+        uri: &'static str,
+        file: &'static str,
+        line: u32,
+        label: &str,
+        value: &T,
+    ) {
+        let current: String = format!("{value:?}");
+
+        let previous = {
+            let mut previous = self.previous.lock().unwrap_or_else(PoisonError::into_inner);
+
+            if previous.as_ref() == Some(&current) {
+                return;
+            }
+
+            previous.replace(current.clone())
+        };
+
+        match previous {
+            None => crate::dirty_log_message(uri, file, line, format_args!("{label} = {current}")),
+            Some(previous) => crate::dirty_log_message(
+                uri,
+                file,
+                line,
+                format_args!("{label}: {previous} → {current}"),
+            ),
+        }
+    }
+}
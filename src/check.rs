@@ -0,0 +1,121 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::LazyLock;
+
+/// The pass/fail counters of every `ddbg_check!()` label, indexed by uri and label.
+static DIRTY_CHECKS: LazyLock<DashMap<(&str, &str), CheckCounters>> = LazyLock::new(DashMap::new);
+
+#[derive(Default)]
+struct CheckCounters {
+    passed: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Checks an invariant without logging anything.  Instead, it counts how many times the check
+/// passed and failed for each label, and a summary is written by [`report()`](crate::report) or
+/// when the process exits:
+///
+/// ```text
+/// [dirty-debug] check "non-negative balance": 37 failed, 999963 passed
+/// ```
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_check;
+/// # let balance: i64 = 0;
+/// #
+/// ddbg_check!("/tmp/log", balance >= 0, "non-negative balance");
+/// ```
+#[macro_export]
+macro_rules! ddbg_check {
+    ($uri:expr, $cond:expr, $label:literal $(,)?) => {{
+        $crate::dirty_check($uri, $label, $cond);
+    }};
+}
+
+/// Like [`assert!()`](std::assert), but when the condition is false it logs the failure instead of
+/// panicking, and execution continues.  Useful in code that runs inside a host process, which a
+/// panic would bring down.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_assert;
+/// # let (balance, account) = (0, 7);
+/// #
+/// ddbg_assert!("/tmp/log", balance >= 0);
+/// ddbg_assert!("/tmp/log", balance >= 0, "account {}", account);
+/// ```
+///
+/// When the assertion fails this logs `assertion failed: balance >= 0: account 7`.
+#[macro_export]
+macro_rules! ddbg_assert {
+    ($uri:expr, $cond:expr $(,)?) => {{
+        if !$cond {
+            $crate::dirty_log_error(
+                $uri,
+                ::std::file!(),
+                ::std::line!(),
+                ::std::format_args!("assertion failed: {}", ::std::stringify!($cond)),
+            );
+        }
+    }};
+    ($uri:expr, $cond:expr, $($arg:tt)+) => {{
+        if !$cond {
+            $crate::dirty_log_error(
+                $uri,
+                ::std::file!(),
+                ::std::line!(),
+                ::std::format_args!(
+                    "assertion failed: {}: {}",
+                    ::std::stringify!($cond),
+                    ::std::format_args!($($arg)+),
+                ),
+            );
+        }
+    }};
+}
+
+/// Counts a `ddbg_check!()`.
+#[doc(hidden)]
+pub fn dirty_check(uri: &'static str, label: &'static str, passed: bool) {
+    let counters = DIRTY_CHECKS.get(&(uri, label)).unwrap_or_else(|| {
+        crate::report::report_on_exit();
+        DIRTY_CHECKS.entry((uri, label)).or_default().downgrade()
+    });
+
+    let counter = match passed {
+        true => &counters.passed,
+        false => &counters.failed,
+    };
+
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn report() {
+    let mut checks: Vec<(&'static str, &'static str, u64, u64)> = DIRTY_CHECKS
+        .iter()
+        .map(|entry| {
+            let (uri, label) = *entry.key();
+            let passed = entry.value().passed.load(Ordering::Relaxed);
+            let failed = entry.value().failed.load(Ordering::Relaxed);
+
+            (uri, label, passed, failed)
+        })
+        .collect();
+
+    checks.sort_unstable();
+
+    for (uri, label, passed, failed) in checks {
+        crate::dirty_log_meta(
+            uri,
+            format_args!("check \"{label}\": {failed} failed, {passed} passed"),
+        );
+    }
+}
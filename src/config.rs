@@ -0,0 +1,156 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Global configuration, read from environment variables.
+
+use std::str::FromStr;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+const DEFAULT_SLOW_OPEN_THRESHOLD: Duration = Duration::from_millis(100);
+
+static SLOW_OPEN_THRESHOLD: LazyLock<Duration> = LazyLock::new(|| {
+    env_var("DIRTY_DEBUG_SLOW_OPEN_MS").map_or(DEFAULT_SLOW_OPEN_THRESHOLD, Duration::from_millis)
+});
+
+static MAX_OUTPUT: LazyLock<Option<u64>> =
+    LazyLock::new(|| env_var("DIRTY_DEBUG_MAX_OUTPUT").map(|ByteSize(bytes)| bytes));
+
+static BANNER: LazyLock<bool> = LazyLock::new(|| env_var("DIRTY_DEBUG_BANNER").unwrap_or(false));
+
+static TIMESTAMP: LazyLock<Option<Timestamp>> = LazyLock::new(|| env_var("DIRTY_DEBUG_TIMESTAMP"));
+
+static DELTA: LazyLock<bool> = LazyLock::new(|| env_var("DIRTY_DEBUG_DELTA").unwrap_or(false));
+
+static THREAD: LazyLock<bool> = LazyLock::new(|| env_var("DIRTY_DEBUG_THREAD").unwrap_or(false));
+
+static PID: LazyLock<bool> = LazyLock::new(|| env_var("DIRTY_DEBUG_PID").unwrap_or(false));
+
+/// The kind of timestamp that starts every line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Timestamp {
+    /// The wall-clock time, in UTC, e.g. `2026-10-16T12:34:56.123456Z`.
+    Utc,
+    /// The time since the process first logged, e.g. `+12.345678s`.
+    Uptime,
+}
+
+impl FromStr for Timestamp {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Timestamp, ()> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "utc" | "rfc3339" => Ok(Timestamp::Utc),
+            "uptime" => Ok(Timestamp::Uptime),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A number of bytes with an optional binary unit suffix, e.g. `512K` or `1G`.
+pub(crate) struct ByteSize(pub(crate) u64);
+
+impl FromStr for ByteSize {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<ByteSize, ()> {
+        let s = s.trim();
+        let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+
+        let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" | "KIB" => 1 << 10,
+            "M" | "MB" | "MIB" => 1 << 20,
+            "G" | "GB" | "GIB" => 1 << 30,
+            "T" | "TB" | "TIB" => 1 << 40,
+            _ => return Err(()),
+        };
+
+        u64::from_str(number).ok().and_then(|n| n.checked_mul(multiplier)).map(ByteSize).ok_or(())
+    }
+}
+
+/// Reads and parses an environment variable.  If it is set but cannot be parsed we warn about it
+/// and ignore it.
+fn env_var<T: FromStr>(name: &str) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+
+    let parsed = T::from_str(&value).ok();
+
+    if parsed.is_none() {
+        eprintln!("dirty-debug: ignoring invalid value of {name}: \"{value}\"");
+    }
+
+    parsed
+}
+
+/// If opening a sink takes longer than this we warn about it in `stderr`
+/// (`DIRTY_DEBUG_SLOW_OPEN_MS`).
+pub(crate) fn slow_open_threshold() -> Duration {
+    *SLOW_OPEN_THRESHOLD
+}
+
+/// The maximum number of bytes of messages the process logs (`DIRTY_DEBUG_MAX_OUTPUT`).
+pub(crate) fn max_output() -> Option<u64> {
+    *MAX_OUTPUT
+}
+
+/// Whether we start every log with a line identifying the process and its build
+/// (`DIRTY_DEBUG_BANNER`).
+pub(crate) fn banner() -> bool {
+    *BANNER
+}
+
+/// The timestamp that starts every line, if any (`DIRTY_DEBUG_TIMESTAMP`).
+pub(crate) fn timestamp() -> Option<Timestamp> {
+    *TIMESTAMP
+}
+
+/// Whether every line starts with the time since the previous line written to the same destination
+/// (`DIRTY_DEBUG_DELTA`).
+pub(crate) fn delta() -> bool {
+    *DELTA
+}
+
+/// Whether every line starts with the name and id of the thread that logged it
+/// (`DIRTY_DEBUG_THREAD`).
+pub(crate) fn thread() -> bool {
+    *THREAD
+}
+
+/// Whether every line starts with the name and id of the process that logged it
+/// (`DIRTY_DEBUG_PID`).
+pub(crate) fn pid() -> bool {
+    *PID
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_byte_size() {
+        let parse = |s: &str| ByteSize::from_str(s).map(|ByteSize(bytes)| bytes);
+
+        assert_eq!(parse("1000"), Ok(1000));
+        assert_eq!(parse("512K"), Ok(512 * 1024));
+        assert_eq!(parse("1G"), Ok(1 << 30));
+        assert_eq!(parse("2mb"), Ok(2 << 20));
+        assert_eq!(parse("1GiB"), Ok(1 << 30));
+        assert!(parse("").is_err());
+        assert!(parse("G").is_err());
+        assert!(parse("1X").is_err());
+        assert!(parse("99999999999T").is_err());
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(Timestamp::from_str("utc"), Ok(Timestamp::Utc));
+        assert_eq!(Timestamp::from_str("RFC3339"), Ok(Timestamp::Utc));
+        assert_eq!(Timestamp::from_str("uptime"), Ok(Timestamp::Uptime));
+        assert!(Timestamp::from_str("").is_err());
+        assert!(Timestamp::from_str("local").is_err());
+    }
+}
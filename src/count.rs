@@ -0,0 +1,67 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::LazyLock;
+
+/// The counter of every `ddbg_count!()` label, indexed by uri and label.
+static DIRTY_COUNTS: LazyLock<DashMap<(&str, &str), AtomicU64>> = LazyLock::new(DashMap::new);
+
+/// Counts how many times a path was taken, without logging anything.  The value of each counter is
+/// written by [`report()`](crate::report) or when the process exits:
+///
+/// ```text
+/// [dirty-debug] branch_a = 1337
+/// ```
+///
+/// Call [`report()`](crate::report) whenever you want to see the counters so far, e.g. every
+/// second from a thread of your own.  With `statsd://` the counters are sent as gauges.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_count;
+/// # let cached = true;
+/// #
+/// match cached {
+///     true => ddbg_count!("/tmp/log", "cache_hit"),
+///     false => ddbg_count!("/tmp/log", "cache_miss"),
+/// }
+/// ```
+#[macro_export]
+macro_rules! ddbg_count {
+    ($uri:expr, $label:literal $(,)?) => {{
+        $crate::dirty_count($uri, $label);
+    }};
+}
+
+/// Counts a `ddbg_count!()`.
+#[doc(hidden)]
+pub fn dirty_count(uri: &'static str, label: &'static str) {
+    let counter = DIRTY_COUNTS.get(&(uri, label)).unwrap_or_else(|| {
+        crate::report::report_on_exit();
+        DIRTY_COUNTS.entry((uri, label)).or_default().downgrade()
+    });
+
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn report() {
+    let mut counts: Vec<(&'static str, &'static str, u64)> = DIRTY_COUNTS
+        .iter()
+        .map(|entry| {
+            let (uri, label) = *entry.key();
+
+            (uri, label, entry.value().load(Ordering::Relaxed))
+        })
+        .collect();
+
+    counts.sort_unstable();
+
+    for (uri, label, count) in counts {
+        crate::dirty_log_meta(uri, format_args!("{label} = {count}"));
+    }
+}
@@ -0,0 +1,194 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::append_entry;
+use dashmap::DashMap;
+use std::fmt;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::sync::LazyLock;
+
+/// Files we are writing CSV rows to, indexed by their path.
+static DIRTY_CSV_FILES: LazyLock<DashMap<&str, CsvFile>> = LazyLock::new(DashMap::new);
+
+/// Appends a row to a CSV file, e.g. to plot it in a spreadsheet.  The header, with the names of
+/// the columns, is written when the file is created.  Values are formatted with
+/// [`Display`](std::fmt::Display), or with [`Debug`](std::fmt::Debug) if they are prefixed by `?`,
+/// and are quoted if needed.
+///
+/// Every row written to a file must have the same columns.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_csv;
+/// # let (t, x, y) = (0.1, 3, Some(4));
+/// #
+/// ddbg_csv!("/tmp/samples.csv", time = t, x = x, y = ?y);
+/// ```
+///
+/// This creates a file starting with `time,x,y`, followed by rows like `0.1,3,Some(4)`.
+#[macro_export]
+macro_rules! ddbg_csv {
+    (@columns $row:ident $(,)?) => {};
+    (@columns $row:ident, $column:ident = ?$value:expr $(, $($rest:tt)*)?) => {
+        $row.push_debug(::std::stringify!($column), &$value);
+        $($crate::ddbg_csv!(@columns $row, $($rest)*);)?
+    };
+    (@columns $row:ident, $column:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $row.push_display(::std::stringify!($column), &$value);
+        $($crate::ddbg_csv!(@columns $row, $($rest)*);)?
+    };
+    ($uri:expr, $($columns:tt)+) => {{
+        let mut row = $crate::CsvRow::new();
+
+        $crate::ddbg_csv!(@columns row, $($columns)+);
+
+        row.write($uri);
+    }};
+}
+
+/// Appends `field` to `out`, quoted if it has commas, quotes or line breaks.
+fn push_field(out: &mut String, field: &str) {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        }
+        false => out.push_str(field),
+    }
+}
+
+/// A row of a `ddbg_csv!()`.
+#[doc(hidden)]
+pub struct CsvRow {
+    header: String,
+    row: String,
+    value: String,
+}
+
+impl CsvRow {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> CsvRow {
+        CsvRow { header: String::new(), row: String::new(), value: String::new() }
+    }
+
+    pub fn push_display<T: fmt::Display + ?Sized>(&mut self, column: &str, value: &T) {
+        self.value.clear();
+        let _ = write!(self.value, "{value}");
+        self.push_value(column);
+    }
+
+    pub fn push_debug<T: fmt::Debug + ?Sized>(&mut self, column: &str, value: &T) {
+        self.value.clear();
+        let _ = write!(self.value, "{value:?}");
+        self.push_value(column);
+    }
+
+    fn push_value(&mut self, column: &str) {
+        if !self.header.is_empty() {
+            self.header.push(',');
+            self.row.push(',');
+        }
+
+        push_field(&mut self.header, column);
+        push_field(&mut self.row, &self.value);
+    }
+
+    pub fn write(mut self, uri: &'static str) {
+        self.row.push('\n');
+
+        if let Err(e) = write_row(uri, &self) {
+            crate::report_failure(uri, &e);
+        }
+    }
+}
+
+struct CsvFile {
+    file: File,
+    header: String,
+}
+
+impl CsvFile {
+    fn open(filepath: &str, header: &str) -> io::Result<CsvFile> {
+        let mut file = File::options().read(true).create(true).append(true).open(filepath)?;
+
+        if file.metadata()?.len() == 0 {
+            append_entry(&mut file, format!("{header}\n").as_bytes())?;
+        } else {
+            let mut existing_header = String::new();
+
+            BufReader::new(&file).read_line(&mut existing_header)?;
+
+            let existing_header = existing_header.trim_end_matches(['\r', '\n']);
+
+            if existing_header != header {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("the file has header \"{existing_header}\", not \"{header}\""),
+                ));
+            }
+        }
+
+        Ok(CsvFile { file, header: header.to_owned() })
+    }
+}
+
+fn write_row(uri: &'static str, row: &CsvRow) -> io::Result<()> {
+    let filepath = uri.strip_prefix("file://").unwrap_or(uri);
+    let mut entry =
+        DIRTY_CSV_FILES.entry(uri).or_try_insert_with(|| CsvFile::open(filepath, &row.header))?;
+    let csv_file = entry.value_mut();
+
+    if csv_file.header != row.header {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the columns of the row do not match the header \"{}\"", csv_file.header),
+        ));
+    }
+
+    append_entry(&mut csv_file.file, row.row.as_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_row() {
+        let mut row = CsvRow::new();
+
+        row.push_display("plain", &1.5);
+        row.push_display("comma", "a,b");
+        row.push_debug("quotes", "hi");
+        row.push_display("line break", "a\nb");
+
+        assert_eq!(row.header, "plain,comma,quotes,line break");
+        assert_eq!(row.row, "1.5,\"a,b\",\"\"\"hi\"\"\",\"a\nb\"");
+    }
+
+    #[test]
+    fn test_open_existing() {
+        let filepath =
+            std::env::temp_dir().join(format!("test_csv_open_existing_{}", std::process::id()));
+        let filepath = filepath.to_str().unwrap();
+
+        std::fs::write(filepath, "i,square\n0,0\n").unwrap();
+
+        let same_columns = CsvFile::open(filepath, "i,square").map(|csv_file| csv_file.header);
+        let other_columns = CsvFile::open(filepath, "i,cube").map(|csv_file| csv_file.header);
+        let contents = std::fs::read_to_string(filepath).unwrap();
+
+        std::fs::remove_file(filepath).unwrap();
+
+        assert_eq!(same_columns.unwrap(), "i,square");
+        assert_eq!(other_columns.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(contents, "i,square\n0,0\n");
+    }
+}
@@ -0,0 +1,238 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
+use std::io::Write;
+
+/// Writes a graph to a [Graphviz](https://graphviz.org) DOT file of its own, and logs its path.
+/// Render it with e.g. `dot -Tsvg graph.dot > graph.svg`.
+///
+/// The graph must implement [`ToDot`], which is already the case for adjacency lists
+/// (`HashMap<K, Vec<K>>` and `BTreeMap<K, Vec<K>>`).  As with [`ddbg_bytes!()`](crate::ddbg_bytes),
+/// the `{n}` in the file path is replaced by a number that makes it a new file.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_dot;
+/// # use std::collections::BTreeMap;
+/// #
+/// let dependencies = BTreeMap::from([("app", vec!["http", "json"]), ("http", vec!["json"])]);
+///
+/// ddbg_dot!("/tmp/log", "/tmp/deps_{n}.dot", &dependencies);
+/// ```
+///
+/// This logs lines like `&dependencies: 3 nodes and 3 edges written to "/tmp/deps_0.dot"`.
+#[macro_export]
+macro_rules! ddbg_dot {
+    ($uri:expr, $filepath:expr, $graph:expr $(,)?) => {{
+        $crate::dirty_log_dot(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($graph),
+            ::std::convert::AsRef::<str>::as_ref(&$filepath),
+            $graph,
+        );
+    }};
+}
+
+/// A graph that can be written with [`ddbg_dot!()`](crate::ddbg_dot).
+///
+/// # Example
+///
+/// ```rust
+/// # use dirty_debug::{DotGraph, ToDot};
+/// #
+/// struct Task {
+///     name: &'static str,
+///     blocked_by: Vec<usize>,
+/// }
+///
+/// struct Tasks(Vec<Task>);
+///
+/// impl ToDot for Tasks {
+///     fn to_dot(&self, graph: &mut DotGraph) {
+///         for (i, task) in self.0.iter().enumerate() {
+///             graph.node(i, task.name);
+///
+///             for blocker in &task.blocked_by {
+///                 graph.edge(blocker, i);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub trait ToDot {
+    /// Adds the nodes and edges of `self` to the graph.
+    fn to_dot(&self, graph: &mut DotGraph);
+}
+
+/// The nodes and edges of a directed graph, in the DOT language.
+pub struct DotGraph {
+    statements: String,
+    nodes: usize,
+    edges: usize,
+}
+
+/// Appends `id` to `out` as a DOT string, quotes included.
+fn push_id(out: &mut String, id: impl fmt::Display) {
+    out.push('"');
+
+    for c in id.to_string().chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+impl DotGraph {
+    fn new() -> DotGraph {
+        DotGraph { statements: String::new(), nodes: 0, edges: 0 }
+    }
+
+    /// Adds a node with a label.  Nodes that are only used in edges are added implicitly, with
+    /// their id as the label.
+    pub fn node(&mut self, id: impl fmt::Display, label: impl fmt::Display) {
+        self.statements.push_str("  ");
+        push_id(&mut self.statements, id);
+        self.statements.push_str(" [label=");
+        push_id(&mut self.statements, label);
+        self.statements.push_str("];\n");
+        self.nodes += 1;
+    }
+
+    /// Adds an edge.
+    pub fn edge(&mut self, from: impl fmt::Display, to: impl fmt::Display) {
+        self.statements.push_str("  ");
+        push_id(&mut self.statements, from);
+        self.statements.push_str(" -> ");
+        push_id(&mut self.statements, to);
+        self.statements.push_str(";\n");
+        self.edges += 1;
+    }
+
+    /// Adds an edge with a label.
+    pub fn labeled_edge(
+        &mut self,
+        from: impl fmt::Display,
+        to: impl fmt::Display,
+        label: impl fmt::Display,
+    ) {
+        self.statements.push_str("  ");
+        push_id(&mut self.statements, from);
+        self.statements.push_str(" -> ");
+        push_id(&mut self.statements, to);
+        self.statements.push_str(" [label=");
+        push_id(&mut self.statements, label);
+        self.statements.push_str("];\n");
+        self.edges += 1;
+    }
+
+    fn to_dot_file(&self) -> String {
+        format!("digraph {{\n{}}}\n", self.statements)
+    }
+}
+
+/// Adds an adjacency list to the graph, with the nodes formatted with `Debug`.
+fn push_adjacency_list<'a, K: fmt::Debug + 'a>(
+    graph: &mut DotGraph,
+    adjacency_list: impl Iterator<Item = (&'a K, &'a Vec<K>)>,
+) {
+    for (node, neighbors) in adjacency_list {
+        let id = format!("{node:?}");
+
+        graph.node(&id, &id);
+
+        for neighbor in neighbors {
+            graph.edge(&id, format!("{neighbor:?}"));
+        }
+    }
+}
+
+impl<K: fmt::Debug, S: BuildHasher> ToDot for HashMap<K, Vec<K>, S> {
+    fn to_dot(&self, graph: &mut DotGraph) {
+        push_adjacency_list(graph, self.iter());
+    }
+}
+
+impl<K: fmt::Debug> ToDot for BTreeMap<K, Vec<K>> {
+    fn to_dot(&self, graph: &mut DotGraph) {
+        push_adjacency_list(graph, self.iter());
+    }
+}
+
+impl<T: ToDot + ?Sized> ToDot for &T {
+    fn to_dot(&self, graph: &mut DotGraph) {
+        (**self).to_dot(graph);
+    }
+}
+
+/// Logs a `ddbg_dot!()`.
+#[doc(hidden)]
+pub fn dirty_log_dot(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    filepath_template: &str,
+    graph: impl ToDot,
+) {
+    let mut dot_graph = DotGraph::new();
+
+    graph.to_dot(&mut dot_graph);
+
+    let DotGraph { nodes, edges, .. } = dot_graph;
+    let result =
+        crate::bytes::create_dump_file(filepath_template).and_then(|(mut dump, filepath)| {
+            dump.write_all(dot_graph.to_dot_file().as_bytes()).map(|()| filepath)
+        });
+
+    match result {
+        Ok(filepath) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label}: {nodes} nodes and {edges} edges written to \"{filepath}\""),
+        ),
+        Err(e) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label}: cannot write graph to \"{filepath_template}\": {e}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_dot_file() {
+        let mut graph = DotGraph::new();
+
+        BTreeMap::from([("a", vec!["b"]), ("b", vec![])]).to_dot(&mut graph);
+        graph.labeled_edge(1, "say \"hi\"\\", "x\ny");
+
+        let expected = r#"digraph {
+  "\"a\"" [label="\"a\""];
+  "\"a\"" -> "\"b\"";
+  "\"b\"" [label="\"b\""];
+  "1" -> "say \"hi\"\\" [label="x\ny"];
+}
+"#;
+
+        assert_eq!(graph.to_dot_file(), expected);
+        assert_eq!((graph.nodes, graph.edges), (2, 2));
+    }
+}
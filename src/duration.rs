@@ -0,0 +1,117 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::time::Duration;
+
+/// Logs a duration in a format that is easy to read at a glance, like `1.23ms` or `4m07s` (see
+/// [`HumanDuration`]).  The duration is a [`Duration`](std::time::Duration) or a number of
+/// nanoseconds.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_dur;
+/// # let start = std::time::Instant::now();
+/// #
+/// ddbg_dur!("/tmp/log", "request", start.elapsed());
+/// ddbg_dur!("/tmp/log", "timeout", 1_500_000_000_u64);
+/// ```
+///
+/// This logs lines like `request: 12.31ms` and `timeout: 1.50s`.
+#[macro_export]
+macro_rules! ddbg_dur {
+    ($uri:expr, $label:expr, $duration:expr $(,)?) => {{
+        $crate::ddbg!($uri, "{}: {}", $label, $crate::HumanDuration::from($duration));
+    }};
+}
+
+/// Formats a duration with the unit that suits it, e.g. `830ns`, `12.50µs`, `1.23ms`, `4.20s`,
+/// `4m07s`, `2h05m00s` or `3d04h10m`.  Use it to format durations in any message:
+///
+/// ```rust,no_run
+/// # use dirty_debug::{ddbg, HumanDuration};
+/// # let (frame, elapsed) = (1, std::time::Duration::ZERO);
+/// #
+/// ddbg!("/tmp/log", "frame {} took {}", frame, HumanDuration::from(elapsed));
+/// ```
+///
+/// It converts from a [`Duration`], or from a number of nanoseconds.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HumanDuration(pub Duration);
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> HumanDuration {
+        HumanDuration(duration)
+    }
+}
+
+impl From<u64> for HumanDuration {
+    fn from(nanos: u64) -> HumanDuration {
+        HumanDuration(Duration::from_nanos(nanos))
+    }
+}
+
+impl From<u128> for HumanDuration {
+    fn from(nanos: u128) -> HumanDuration {
+        let secs = u64::try_from(nanos / 1_000_000_000).unwrap_or(u64::MAX);
+        let subsec_nanos = u32::try_from(nanos % 1_000_000_000).unwrap_or(0);
+
+        HumanDuration(Duration::new(secs, subsec_nanos))
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        let secs = self.0.as_secs();
+
+        // Formats `nanos` in `unit`s with two decimal places, rounded.
+        let decimal = |f: &mut fmt::Formatter<'_>, unit: u128, suffix: &str| {
+            let hundredths = (nanos * 100 + unit / 2) / unit;
+
+            write!(f, "{}.{:02}{suffix}", hundredths / 100, hundredths % 100)
+        };
+
+        match secs {
+            _ if nanos < 1_000 => write!(f, "{nanos}ns"),
+            _ if nanos < 999_995 => decimal(f, 1_000, "µs"),
+            _ if nanos < 999_995_000 => decimal(f, 1_000_000, "ms"),
+            _ if nanos < 59_995_000_000 => decimal(f, 1_000_000_000, "s"),
+            // From 59.995s on the seconds would be rounded to 60.00s, so that is a minute already.
+            0..=3_599 => write!(f, "{}m{:02}s", secs.max(60) / 60, secs.max(60) % 60),
+            3_600..=86_399 => write!(f, "{}h{:02}m{:02}s", secs / 3_600, secs / 60 % 60, secs % 60),
+            _ => write!(f, "{}d{:02}h{:02}m", secs / 86_400, secs / 3_600 % 24, secs / 60 % 60),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_human_duration() {
+        let human = |nanos: u64| HumanDuration::from(nanos).to_string();
+
+        assert_eq!(human(0), "0ns");
+        assert_eq!(human(830), "830ns");
+        assert_eq!(human(12_504), "12.50µs");
+        assert_eq!(human(999_994), "999.99µs");
+        assert_eq!(human(999_995), "1.00ms");
+        assert_eq!(human(1_234_567), "1.23ms");
+        assert_eq!(human(999_995_000), "1.00s");
+        assert_eq!(human(4_204_000_000), "4.20s");
+        assert_eq!(human(59_994_000_000), "59.99s");
+        assert_eq!(human(59_995_000_000), "1m00s");
+        assert_eq!(human(59_999_000_000), "1m00s");
+        assert_eq!(human(60_000_000_000), "1m00s");
+        assert_eq!(human(247_000_000_000), "4m07s");
+        assert_eq!(human(7_500_000_000_000), "2h05m00s");
+        assert_eq!(human(274_200_000_000_000), "3d04h10m");
+        assert_eq!(HumanDuration::from(1_500_u128).to_string(), "1.50µs");
+        assert_eq!(HumanDuration::from(Duration::from_millis(20)).to_string(), "20.00ms");
+    }
+}
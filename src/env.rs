@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+/// Logs the environment variables of the process, sorted by name, one per line.  If a prefix is
+/// given, only the variables whose names start with it are logged.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_env;
+/// #
+/// ddbg_env!("/tmp/log");
+/// ddbg_env!("/tmp/log", "RUST_");
+/// ```
+///
+/// This logs lines like `RUST_LOG="debug"`.
+#[macro_export]
+macro_rules! ddbg_env {
+    ($uri:expr $(,)?) => {{
+        $crate::dirty_log_env($uri, ::std::file!(), ::std::line!(), "");
+    }};
+    ($uri:expr, $prefix:expr $(,)?) => {{
+        $crate::dirty_log_env($uri, ::std::file!(), ::std::line!(), $prefix);
+    }};
+}
+
+/// Logs a `ddbg_env!()`.
+#[doc(hidden)]
+pub fn dirty_log_env(uri: &'static str, file: &'static str, line: u32, prefix: &str) {
+    let mut vars: Vec<(String, String)> = std::env::vars_os()
+        .map(|(name, value)| {
+            (name.to_string_lossy().into_owned(), value.to_string_lossy().into_owned())
+        })
+        .filter(|(name, _)| name.starts_with(prefix))
+        .collect();
+
+    vars.sort_unstable();
+
+    if vars.is_empty() {
+        crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("no environment variables start with \"{prefix}\""),
+        );
+    }
+
+    for (name, value) in vars {
+        crate::dirty_log_message(uri, file, line, format_args!("{name}={value:?}"));
+    }
+}
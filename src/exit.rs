@@ -0,0 +1,112 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::ffi::c_int;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Mutex, Once, PoisonError};
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> c_int;
+}
+
+// Unlike `atexit()`, glibc's `on_exit()` tells us the exit status.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+extern "C" {
+    #[link_name = "on_exit"]
+    fn glibc_on_exit(
+        callback: extern "C" fn(c_int, *mut std::ffi::c_void),
+        arg: *mut std::ffi::c_void,
+    ) -> c_int;
+}
+
+/// Callbacks to run when the process exits, in the order they were registered.
+static DIRTY_EXIT_CALLBACKS: Mutex<Vec<fn()>> = Mutex::new(Vec::new());
+
+/// A callback to run after all the others.
+static DIRTY_EXIT_LAST_CALLBACK: Mutex<Option<fn()>> = Mutex::new(None);
+
+/// The exit status, or `UNKNOWN_STATUS` if it is not known (yet).
+static EXIT_STATUS: AtomicI32 = AtomicI32::new(UNKNOWN_STATUS);
+
+const UNKNOWN_STATUS: i32 = i32::MIN;
+
+extern "C" fn run_exit_callbacks() {
+    let callbacks: Vec<fn()> =
+        std::mem::take(&mut *DIRTY_EXIT_CALLBACKS.lock().unwrap_or_else(PoisonError::into_inner));
+    let last_callback: Option<fn()> =
+        DIRTY_EXIT_LAST_CALLBACK.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+    // The sinks are flushed last, so that nothing logged by the callbacks is lost.
+    for callback in
+        callbacks.into_iter().chain(last_callback).chain([crate::sink::flush_all as fn()])
+    {
+        // We cannot let a panic unwind out of an `extern "C"` function.
+        let _result = std::panic::catch_unwind(callback);
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+extern "C" fn run_exit_callbacks_with_status(status: c_int, _arg: *mut std::ffi::c_void) {
+    EXIT_STATUS.store(status, Ordering::Relaxed);
+    run_exit_callbacks();
+}
+
+fn register_atexit() {
+    static REGISTER_ATEXIT: Once = Once::new();
+
+    REGISTER_ATEXIT.call_once(|| {
+        // SAFETY: `run_exit_callbacks` is a valid function that never unwinds.  If this fails the
+        // callbacks will simply not run.
+        #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+        unsafe {
+            atexit(run_exit_callbacks)
+        };
+
+        // SAFETY: `run_exit_callbacks_with_status` is a valid function that never unwinds and
+        // ignores its argument.  If this fails the callbacks will simply not run.
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        unsafe {
+            glibc_on_exit(run_exit_callbacks_with_status, std::ptr::null_mut())
+        };
+    });
+}
+
+/// Makes sure the sinks are flushed when the process exits normally.
+pub(crate) fn flush_sinks_on_exit() {
+    register_atexit();
+}
+
+/// Registers a callback to run when the process exits normally (i.e. when `main()` returns or
+/// `std::process::exit()` is called).  Registering the same callback more than once has no effect.
+/// The sinks are flushed after all callbacks run.
+pub(crate) fn on_exit(callback: fn()) {
+    register_atexit();
+
+    let mut callbacks = DIRTY_EXIT_CALLBACKS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if !callbacks.contains(&callback) {
+        callbacks.push(callback);
+    }
+}
+
+/// Like [`on_exit()`], but the callback runs after all the callbacks registered with it.  There
+/// can only be one such callback.
+pub(crate) fn on_exit_last(callback: fn()) {
+    register_atexit();
+
+    *DIRTY_EXIT_LAST_CALLBACK.lock().unwrap_or_else(PoisonError::into_inner) = Some(callback);
+}
+
+/// Forgets the callbacks registered so far, so they do not run when the process exits.
+pub(crate) fn forget_callbacks() {
+    DIRTY_EXIT_CALLBACKS.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    *DIRTY_EXIT_LAST_CALLBACK.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// The exit status of the process, if it is exiting and the platform lets us know it.
+pub(crate) fn status() -> Option<i32> {
+    Some(EXIT_STATUS.load(Ordering::Relaxed)).filter(|&status| status != UNKNOWN_STATUS)
+}
@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::backtrace::Backtrace;
+use std::fmt;
+
+/// Logs a message, with the name of the current thread and its backtrace, then aborts the process.
+/// Before aborting, the statistics are reported (see [`report()`](crate::report)) and every
+/// destination is flushed, so nothing logged so far is lost.
+///
+/// Unlike a panic, this cannot be caught or swallowed by a host, and no destructors run.  Useful
+/// to guarantee that the process stops right where something went wrong.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_fatal;
+/// # let refcount = -1;
+/// #
+/// if refcount < 0 {
+///     ddbg_fatal!("/tmp/log", "refcount underflow: {}", refcount);
+/// }
+/// ```
+#[macro_export]
+macro_rules! ddbg_fatal {
+    ($uri:expr, $($arg:tt)+) => {
+        $crate::dirty_fatal($uri, ::std::file!(), ::std::line!(), ::std::format_args!($($arg)+))
+    };
+}
+
+/// Logs the message of a `ddbg_fatal!()`, and flushes everything.
+fn log_fatal(uri: &'static str, file: &'static str, line: u32, args: fmt::Arguments<'_>) {
+    let thread = std::thread::current();
+    let thread_name = thread.name().map_or_else(|| format!("{:?}", thread.id()), str::to_owned);
+    let backtrace = Backtrace::force_capture();
+
+    crate::dirty_log_error(
+        uri,
+        file,
+        line,
+        format_args!("fatal: {args} (aborting in thread \"{thread_name}\")\n{backtrace}"),
+    );
+
+    crate::report::report();
+    crate::sink::flush_all();
+}
+
+/// Logs a `ddbg_fatal!()`, and aborts.
+#[doc(hidden)]
+pub fn dirty_fatal(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    args: fmt::Arguments<'_>,
+) -> ! {
+    log_fatal(uri, file, line, args);
+
+    std::process::abort()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_log_fatal() {
+        // Fatal errors are logged even in a quiet phase.
+        crate::quiet_during("startup", || {
+            log_fatal("mem://test_log_fatal", "src/main.rs", 7, format_args!("bad {}", 42));
+        });
+
+        let log = crate::mem_contents("test_log_fatal");
+        let (open_line, log) = log.split_once('\n').unwrap();
+
+        assert!(open_line.starts_with("[dirty-debug] opening "), "{open_line}");
+
+        assert!(
+            log.starts_with(
+                "[src/main.rs:7] fatal: bad 42 (aborting in thread \"fatal::test::test_log_fatal\")\n"
+            ),
+            "{log}"
+        );
+        assert!(log.lines().count() > 1, "{log}");
+    }
+}
@@ -0,0 +1,34 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+/// Logs a marker with no message: just the `[file:line]` prefix and how many times this call site
+/// was executed, which tells the order and the frequency in which code is reached.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_here;
+/// #
+/// for _ in 0..2 {
+///     ddbg_here!("/tmp/log");
+/// }
+/// ```
+///
+/// This logs `[src/main.rs:4] hit 1` and then `[src/main.rs:4] hit 2`.
+#[macro_export]
+macro_rules! ddbg_here {
+    ($uri:expr $(,)?) => {{
+        static HITS: ::std::sync::atomic::AtomicU64 = ::std::sync::atomic::AtomicU64::new(0);
+
+        let hit: u64 = HITS.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) + 1;
+
+        $crate::dirty_log_message(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::format_args!("hit {hit}"),
+        );
+    }};
+}
@@ -0,0 +1,270 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use dashmap::DashMap;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+/// The histogram of every `ddbg_hist!()` label, indexed by uri and label.
+static DIRTY_HISTOGRAMS: LazyLock<DashMap<(&str, &str), Mutex<Histogram>>> =
+    LazyLock::new(DashMap::new);
+
+/// Every power of two is split into `2^MANTISSA_BITS` buckets.
+const MANTISSA_BITS: u32 = 7;
+/// The bits of an `f64` that are dropped to get its bucket.
+const BUCKET_SHIFT: u32 = f64::MANTISSA_DIGITS - 1 - MANTISSA_BITS;
+
+/// Records a value in a histogram, without logging anything.  The percentiles of each label are
+/// written by [`report()`](crate::report) or when the process exits:
+///
+/// ```text
+/// [dirty-debug] histogram "latency_ms": count=1000 min=0.03 p50=1.25 p90=2.5 p99=4.75 max=5.17
+/// ```
+///
+/// The values can be of any integer or floating-point type, including negative ones.  The
+/// histogram has buckets of exponentially growing width, so the percentiles are approximate: they
+/// are rounded towards zero by at most 0.8% of their value (integers up to 255 are exact).  NaNs
+/// are not part of the percentiles, but they are counted.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_hist;
+/// # let start = std::time::Instant::now();
+/// #
+/// ddbg_hist!("/tmp/log", "latency_ms", start.elapsed().as_secs_f64() * 1000.0);
+/// ```
+#[macro_export]
+macro_rules! ddbg_hist {
+    ($uri:expr, $label:literal, $value:expr $(,)?) => {{
+        $crate::dirty_hist($uri, $label, $crate::HistValue::to_f64($value));
+    }};
+}
+
+/// A value that can be recorded with [`ddbg_hist!()`](crate::ddbg_hist).
+#[doc(hidden)]
+pub trait HistValue {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_hist_value {
+    (lossless: $($lossless:ty),+; lossy: $($lossy:ty),+) => {
+        $(
+            impl HistValue for $lossless {
+                fn to_f64(self) -> f64 {
+                    f64::from(self)
+                }
+            }
+        )+
+        $(
+            impl HistValue for $lossy {
+                // The histogram is approximate anyway.
+                #[allow(clippy::cast_precision_loss)]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )+
+    };
+}
+
+impl_hist_value!(
+    lossless: u8, u16, u32, i8, i16, i32, f32, f64;
+    lossy: u64, u128, usize, i64, i128, isize
+);
+
+/// A histogram with buckets of exponentially growing width, on both sides of zero.
+struct Histogram {
+    /// The number of values in each bucket, indexed by their key (see [`bucket_key()`]).
+    buckets: BTreeMap<i32, u64>,
+    count: u64,
+    nan_count: u64,
+    min: f64,
+    max: f64,
+}
+
+/// The key of the bucket of a value, which are ordered like the values.  The key of a positive
+/// value is the exponent and the most significant bits of the mantissa of its representation.
+fn bucket_key(value: f64) -> i32 {
+    // This has at most 19 bits, so the conversion never fails.
+    let key = i32::try_from(value.abs().to_bits() >> BUCKET_SHIFT).unwrap_or(i32::MAX);
+
+    if value.is_sign_negative() {
+        -key
+    } else {
+        key
+    }
+}
+
+/// The value of the bucket closest to zero.
+fn bucket_value(key: i32) -> f64 {
+    let magnitude = f64::from_bits(u64::from(key.unsigned_abs()) << BUCKET_SHIFT);
+
+    if key < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+impl Histogram {
+    fn new() -> Histogram {
+        Histogram {
+            buckets: BTreeMap::new(),
+            count: 0,
+            nan_count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn record(&mut self, value: f64) {
+        if value.is_nan() {
+            self.nan_count += 1;
+            return;
+        }
+
+        *self.buckets.entry(bucket_key(value)).or_default() += 1;
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// The value below which `percent` of the values are.
+    fn percentile(&self, percent: u64) -> f64 {
+        let rank = (self.count * percent).div_ceil(100).max(1);
+        let mut seen: u64 = 0;
+
+        for (&key, &count) in &self.buckets {
+            seen += count;
+
+            if seen >= rank {
+                return bucket_value(key).clamp(self.min, self.max);
+            }
+        }
+
+        self.max
+    }
+
+    fn summary(&self) -> String {
+        let mut summary = format!("count={}", self.count);
+
+        if self.count > 0 {
+            let _ = write!(
+                summary,
+                " min={} p50={} p90={} p99={} max={}",
+                self.min,
+                self.percentile(50),
+                self.percentile(90),
+                self.percentile(99),
+                self.max,
+            );
+        }
+
+        if self.nan_count > 0 {
+            let _ = write!(summary, " nan={}", self.nan_count);
+        }
+
+        summary
+    }
+}
+
+/// Records a `ddbg_hist!()`.
+#[doc(hidden)]
+pub fn dirty_hist(uri: &'static str, label: &'static str, value: f64) {
+    let histogram = DIRTY_HISTOGRAMS.get(&(uri, label)).unwrap_or_else(|| {
+        crate::report::report_on_exit();
+        DIRTY_HISTOGRAMS
+            .entry((uri, label))
+            .or_insert_with(|| Mutex::new(Histogram::new()))
+            .downgrade()
+    });
+
+    histogram.lock().unwrap_or_else(PoisonError::into_inner).record(value);
+}
+
+pub(crate) fn report() {
+    let mut histograms: Vec<(&'static str, &'static str, String)> = DIRTY_HISTOGRAMS
+        .iter()
+        .map(|entry| {
+            let (uri, label) = *entry.key();
+            let summary = entry.value().lock().unwrap_or_else(PoisonError::into_inner).summary();
+
+            (uri, label, summary)
+        })
+        .collect();
+
+    histograms.sort_unstable();
+
+    for (uri, label, summary) in histograms {
+        crate::dirty_log_meta(uri, format_args!("histogram \"{label}\": {summary}"));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_buckets() {
+        let mut values: Vec<f64> = (0..100_000).map(f64::from).collect();
+
+        values.extend([0.001, 0.75, 1e300, f64::MAX]);
+        values.sort_by(f64::total_cmp);
+
+        for pair in values.windows(2) {
+            assert!(bucket_key(pair[0]) <= bucket_key(pair[1]), "{pair:?}");
+            assert!(bucket_key(-pair[0]) >= bucket_key(-pair[1]), "{pair:?}");
+        }
+
+        for value in values.iter().flat_map(|&value| [value, -value]) {
+            let bucket_value = bucket_value(bucket_key(value));
+
+            assert!(bucket_value.abs() <= value.abs(), "{value}");
+            assert!(value.abs() - bucket_value.abs() <= value.abs() / 128.0, "{value}");
+        }
+
+        for value in (-255..=255).map(f64::from) {
+            assert_eq!(bucket_value(bucket_key(value)).to_bits(), value.to_bits(), "{value}");
+        }
+
+        assert!(bucket_value(bucket_key(f64::INFINITY)).is_infinite());
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut histogram = Histogram::new();
+
+        for value in 1..=1000 {
+            histogram.record(f64::from(value));
+        }
+
+        assert_eq!(histogram.summary(), "count=1000 min=1 p50=500 p90=900 p99=988 max=1000");
+
+        let mut histogram = Histogram::new();
+
+        histogram.record(7.0);
+
+        assert_eq!(histogram.summary(), "count=1 min=7 p50=7 p90=7 p99=7 max=7");
+
+        let mut histogram = Histogram::new();
+
+        for value in [-2.5, -0.5, 0.25, f64::NAN] {
+            histogram.record(value);
+        }
+
+        assert_eq!(
+            histogram.summary(),
+            "count=3 min=-2.5 p50=-0.5 p90=0.25 p99=0.25 max=0.25 nan=1"
+        );
+
+        let mut histogram = Histogram::new();
+
+        histogram.record(f64::NAN);
+
+        assert_eq!(histogram.summary(), "count=0 nan=1");
+    }
+}
@@ -0,0 +1,290 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::io;
+use std::io::Write;
+
+/// Writes a buffer of pixels to an image file of its own, and logs its path, so that you can see
+/// e.g. an intermediate framebuffer.  The file is a PNG, or a binary PPM if the path ends in
+/// `.ppm` (which has no transparency, so the alpha channel is dropped).
+///
+/// The pixels are given row by row, top to bottom, with no padding between rows.  As with
+/// [`ddbg_bytes!()`](crate::ddbg_bytes), the `{n}` in the file path is replaced by a number that
+/// makes it a new file.
+///
+/// The PNG is not compressed, since this is meant to be quick, not small.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::{ddbg_image, PixelFormat};
+/// # let (width, height) = (640, 480);
+/// # let framebuffer = vec![0_u8; width * height * 4];
+/// #
+/// ddbg_image!("/tmp/log", "/tmp/frame_{n}.png", &framebuffer, width, height, PixelFormat::Rgba8);
+/// ```
+///
+/// This logs lines like `&framebuffer: 640x480 image written to "/tmp/frame_0.png"`.
+#[macro_export]
+macro_rules! ddbg_image {
+    ($uri:expr, $filepath:expr, $pixels:expr, $width:expr, $height:expr, $format:expr $(,)?) => {{
+        $crate::dirty_log_image(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($pixels),
+            ::std::convert::AsRef::<str>::as_ref(&$filepath),
+            ::std::convert::AsRef::<[u8]>::as_ref(&$pixels),
+            (u32::try_from($width).unwrap_or(0), u32::try_from($height).unwrap_or(0)),
+            $format,
+        );
+    }};
+}
+
+/// The layout of the pixels of an [`ddbg_image!()`](crate::ddbg_image), with 8 bits per channel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PixelFormat {
+    /// One byte per pixel.
+    Gray8,
+    /// Red, green and blue.
+    Rgb8,
+    /// Red, green, blue and alpha.
+    Rgba8,
+}
+
+impl PixelFormat {
+    fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Gray8 => 1,
+            PixelFormat::Rgb8 => 3,
+            PixelFormat::Rgba8 => 4,
+        }
+    }
+
+    fn png_color_type(self) -> u8 {
+        match self {
+            PixelFormat::Gray8 => 0,
+            PixelFormat::Rgb8 => 2,
+            PixelFormat::Rgba8 => 6,
+        }
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let crc = bytes.iter().fold(!0_u32, |mut crc, &byte| {
+        crc ^= u32::from(byte);
+
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb8_8320,
+                _ => crc >> 1,
+            };
+        }
+
+        crc
+    });
+
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1_u32, 0_u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65_521;
+
+        (a, (b + a) % 65_521)
+    });
+
+    (b << 16) | a
+}
+
+fn push_png_chunk(png: &mut Vec<u8>, kind: [u8; 4], data: &[u8]) {
+    let start = png.len();
+
+    png.extend_from_slice(&u32::try_from(data.len()).unwrap_or(u32::MAX).to_be_bytes());
+    png.extend_from_slice(&kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[start + 4..]);
+
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK_LEN: usize = 65_535;
+
+    let mut zlib = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK_LEN * 5 + 11);
+
+    zlib.extend_from_slice(&[0x78, 0x01]);
+
+    let mut blocks = data.chunks(MAX_BLOCK_LEN).peekable();
+
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let len = u16::try_from(block.len()).unwrap_or(u16::MAX);
+
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+fn png(pixels: &[u8], (width, height): (u32, u32), format: PixelFormat) -> Vec<u8> {
+    let row_len = usize::try_from(width).unwrap_or(0) * format.bytes_per_pixel();
+    let mut scanlines = Vec::with_capacity(pixels.len() + pixels.len() / row_len.max(1));
+
+    for row in pixels.chunks(row_len.max(1)) {
+        // Filter type "none".
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // Bit depth, color type, compression, filter, and interlace methods.
+    header.extend_from_slice(&[8, format.png_color_type(), 0, 0, 0]);
+
+    let mut png = Vec::with_capacity(scanlines.len() + 64);
+
+    png.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+    push_png_chunk(&mut png, *b"IHDR", &header);
+    push_png_chunk(&mut png, *b"IDAT", &zlib_stored(&scanlines));
+    push_png_chunk(&mut png, *b"IEND", &[]);
+    png
+}
+
+fn ppm(pixels: &[u8], (width, height): (u32, u32), format: PixelFormat) -> Vec<u8> {
+    let mut ppm = format!("P6\n{width} {height}\n255\n").into_bytes();
+
+    for pixel in pixels.chunks(format.bytes_per_pixel()) {
+        match format {
+            PixelFormat::Gray8 => ppm.extend_from_slice(&[pixel[0]; 3]),
+            PixelFormat::Rgb8 | PixelFormat::Rgba8 => ppm.extend_from_slice(&pixel[..3]),
+        }
+    }
+
+    ppm
+}
+
+fn write_image(
+    filepath_template: &str,
+    pixels: &[u8],
+    size: (u32, u32),
+    format: PixelFormat,
+) -> io::Result<String> {
+    let (width, height) = size;
+    let bytes_per_pixel = u64::try_from(format.bytes_per_pixel()).unwrap_or(u64::MAX);
+    let expected_len = u64::from(width) * u64::from(height) * bytes_per_pixel;
+
+    if u64::try_from(pixels.len()).ok() != Some(expected_len) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("expected {expected_len} bytes for a {width}x{height} {format:?} image"),
+        ));
+    }
+
+    let image = match filepath_template.to_ascii_lowercase().ends_with(".ppm") {
+        true => ppm(pixels, size, format),
+        false => png(pixels, size, format),
+    };
+    let (mut dump, filepath) = crate::bytes::create_dump_file(filepath_template)?;
+
+    dump.write_all(&image)?;
+
+    Ok(filepath)
+}
+
+/// Logs a `ddbg_image!()`.
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub fn dirty_log_image(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    filepath_template: &str,
+    pixels: &[u8],
+    (width, height): (u32, u32),
+    format: PixelFormat,
+) {
+    match write_image(filepath_template, pixels, (width, height), format) {
+        Ok(filepath) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label}: {width}x{height} image written to \"{filepath}\""),
+        ),
+        Err(e) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label}: cannot write image to \"{filepath_template}\": {e}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_zlib_stored() {
+        let data: Vec<u8> = (0..70_000_u32).map(|i| u8::try_from(i % 251).unwrap()).collect();
+        let zlib = zlib_stored(&data);
+
+        assert_eq!(zlib[..2], [0x78, 0x01]);
+        assert_eq!(zlib[2..7], [0, 0xff, 0xff, 0, 0]);
+        assert_eq!(zlib[65_542..65_547], [1, 0x71, 0x11, 0x8e, 0xee]);
+        assert_eq!(zlib.len(), 2 + 5 + 65_535 + 5 + 4_465 + 4);
+        assert_eq!(zlib_stored(&[]), [0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_png() {
+        let png = png(&[255, 0, 0, 0, 255, 0], (2, 1), PixelFormat::Rgb8);
+
+        let expected_idat_data: Vec<u8> = [
+            &[0x78, 0x01, 1, 7, 0, 0xf8, 0xff, 0, 255, 0, 0, 0, 255, 0][..],
+            &adler32(&[0, 255, 0, 0, 0, 255, 0]).to_be_bytes(),
+        ]
+        .concat();
+
+        assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+        assert_eq!(png[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(png[16..29], [0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        assert_eq!(png[33..37], 18_u32.to_be_bytes());
+        assert_eq!(png[37..41], *b"IDAT");
+        assert_eq!(png[41..59], expected_idat_data);
+        assert_eq!(
+            png[png.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]
+        );
+    }
+
+    #[test]
+    fn test_ppm() {
+        assert_eq!(
+            ppm(&[7, 9], (2, 1), PixelFormat::Gray8),
+            b"P6\n2 1\n255\n\x07\x07\x07\x09\x09\x09"
+        );
+        assert_eq!(ppm(&[1, 2, 3, 4], (1, 1), PixelFormat::Rgba8), b"P6\n1 1\n255\n\x01\x02\x03");
+    }
+}
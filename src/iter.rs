@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+
+/// Wraps an iterator so that every item it yields is logged, with its index, as it flows through.
+/// Nothing is collected, so this can go in the middle of an iterator chain.  With `every = n` only
+/// every `n`-th item is logged (the first, the `n + 1`-th, and so on).
+///
+/// The items are formatted with [`Debug`](std::fmt::Debug).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_iter;
+/// # let words = ["a", "bb", "ccc"];
+/// #
+/// let total: usize = ddbg_iter!("/tmp/log", words.iter().map(|w| w.len())).sum();
+/// let last = ddbg_iter!("/tmp/log", 0..1_000_000, every = 1000).last();
+/// ```
+///
+/// This logs lines like `words.iter().map(|w| w.len())[2] = 3`.
+#[macro_export]
+macro_rules! ddbg_iter {
+    ($uri:expr, $iter:expr $(,)?) => {
+        $crate::ddbg_iter!($uri, $iter, every = 1)
+    };
+    ($uri:expr, $iter:expr, every = $n:expr $(,)?) => {
+        $crate::LoggedIter::new(
+            ::std::iter::IntoIterator::into_iter($iter),
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($iter),
+            $n,
+        )
+    };
+}
+
+/// The iterator returned by [`ddbg_iter!()`](crate::ddbg_iter).
+#[doc(hidden)]
+pub struct LoggedIter<I> {
+    iter: I,
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &'static str,
+    every: usize,
+    index: usize,
+}
+
+impl<I> LoggedIter<I> {
+    #[must_use]
+    pub fn new(
+        iter: I,
+        uri: &'static str,
+        file: &'static str,
+        line: u32,
+        label: &'static str,
+        every: usize,
+    ) -> LoggedIter<I> {
+        LoggedIter { iter, uri, file, line, label, every: every.max(1), index: 0 }
+    }
+}
+
+impl<I: Iterator> Iterator for LoggedIter<I>
+where
+    I::Item: fmt::Debug,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let item = self.iter.next()?;
+
+        if self.index % self.every == 0 {
+            crate::dirty_log_message(
+                self.uri,
+                self.file,
+                self.line,
+                format_args!("{}[{}] = {item:?}", self.label, self.index),
+            );
+        }
+
+        self.index += 1;
+
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
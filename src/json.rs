@@ -0,0 +1,97 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt::Write;
+
+/// Logs the value of an expression serialized as compact JSON, for types that implement
+/// `serde::Serialize` but have no useful `Debug`.  This requires the `serde` feature.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_json;
+/// # let request = vec![("user", 42)];
+/// #
+/// ddbg_json!("/tmp/log", &request);
+/// ```
+///
+/// This logs lines like `&request = [["user",42]]`.
+#[cfg(feature = "serde")]
+#[macro_export]
+macro_rules! ddbg_json {
+    ($uri:expr, $val:expr $(,)?) => {{
+        $crate::dirty_log_json(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($val),
+            &$val,
+        );
+    }};
+}
+
+/// Logs a `ddbg_json!()`.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub fn dirty_log_json<T: serde::Serialize + ?Sized>(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    value: &T,
+) {
+    match serde_json::to_string(value) {
+        Ok(json) => crate::dirty_log_message(uri, file, line, format_args!("{label} = {json}")),
+        Err(e) => {
+            crate::dirty_log_message(
+                uri,
+                file,
+                line,
+                format_args!("{label}: cannot serialize: {e}"),
+            );
+        }
+    }
+}
+
+/// Appends `s` to `out` as a JSON string, quotes included.
+pub(crate) fn push_string(out: &mut String, s: &str) {
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push_string() {
+        let json = |s: &str| {
+            let mut out = String::new();
+            push_string(&mut out, s);
+            out
+        };
+
+        assert_eq!(json(""), r#""""#);
+        assert_eq!(json("plain"), r#""plain""#);
+        assert_eq!(json("say \"hi\"\n"), r#""say \"hi\"\n""#);
+        assert_eq!(json("C:\\dir\t\u{1}"), r#""C:\\dir\t\u0001""#);
+        assert_eq!(json("ünïcödé"), r#""ünïcödé""#);
+    }
+}
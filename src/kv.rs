@@ -0,0 +1,114 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::fmt::Write;
+
+/// Logs key-value pairs in the [logfmt](https://brandur.org/logfmt) style, which is easy to grep
+/// and to parse afterwards.  Values are formatted with [`Display`](std::fmt::Display), or with
+/// [`Debug`](std::fmt::Debug) if they are prefixed by `?`.  Values with spaces, quotes or `=` are
+/// quoted.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_kv;
+/// # let (uid, state, t) = (7, Some("idle"), 12);
+/// #
+/// ddbg_kv!("/tmp/log", user_id = uid, state = ?state, latency_ms = t);
+/// ```
+///
+/// This logs `user_id=7 state="Some(\"idle\")" latency_ms=12`.
+#[macro_export]
+macro_rules! ddbg_kv {
+    (@pairs $logfmt:ident $(,)?) => {};
+    (@pairs $logfmt:ident, $key:ident = ?$value:expr $(, $($rest:tt)*)?) => {
+        $logfmt.push_debug(::std::stringify!($key), &$value);
+        $($crate::ddbg_kv!(@pairs $logfmt, $($rest)*);)?
+    };
+    (@pairs $logfmt:ident, $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $logfmt.push_display(::std::stringify!($key), &$value);
+        $($crate::ddbg_kv!(@pairs $logfmt, $($rest)*);)?
+    };
+    ($uri:expr, $($pairs:tt)+) => {{
+        let mut logfmt = $crate::Logfmt::new();
+
+        $crate::ddbg_kv!(@pairs logfmt, $($pairs)+);
+
+        logfmt.log($uri, ::std::file!(), ::std::line!());
+    }};
+}
+
+/// The pairs of a `ddbg_kv!()`, formatted as logfmt.
+#[doc(hidden)]
+pub struct Logfmt {
+    pub(crate) line: String,
+    value: String,
+}
+
+impl Logfmt {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Logfmt {
+        Logfmt { line: String::new(), value: String::new() }
+    }
+
+    pub fn push_display<T: fmt::Display + ?Sized>(&mut self, key: &str, value: &T) {
+        self.value.clear();
+        let _ = write!(self.value, "{value}");
+        self.push_value(key);
+    }
+
+    pub fn push_debug<T: fmt::Debug + ?Sized>(&mut self, key: &str, value: &T) {
+        self.value.clear();
+        let _ = write!(self.value, "{value:?}");
+        self.push_value(key);
+    }
+
+    fn push_value(&mut self, key: &str) {
+        if !self.line.is_empty() {
+            self.line.push(' ');
+        }
+
+        self.line.push_str(key);
+        self.line.push('=');
+
+        let needs_quotes = self.value.is_empty()
+            || self
+                .value
+                .contains(|c: char| c.is_whitespace() || c.is_control() || "\"=".contains(c));
+
+        match needs_quotes {
+            true => crate::json::push_string(&mut self.line, &self.value),
+            false => self.line.push_str(&self.value),
+        }
+    }
+
+    pub fn log(self, uri: &'static str, file: &'static str, line: u32) {
+        crate::dirty_log_message(uri, file, line, format_args!("{}", self.line));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_logfmt_quoting() {
+        let mut logfmt = Logfmt::new();
+
+        logfmt.push_display("plain", "abc");
+        logfmt.push_display("empty", "");
+        logfmt.push_display("spaces", "a b");
+        logfmt.push_display("equals", "a=b");
+        logfmt.push_debug("quotes", "say \"hi\"");
+        logfmt.push_display("newline", "a\nb");
+
+        assert_eq!(
+            logfmt.line,
+            r#"plain=abc empty="" spaces="a b" equals="a=b" quotes="\"say \\\"hi\\\"\"" newline="a\nb""#
+        );
+    }
+}
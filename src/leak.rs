@@ -0,0 +1,179 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use dashmap::DashMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::LazyLock;
+
+/// How many ids of live instances a report lists, per tag.
+const MAX_REPORTED_IDS: usize = 16;
+
+/// The instances of every `ddbg_leak!()` tag, indexed by uri and tag.
+static DIRTY_LEAKS: LazyLock<DashMap<(&str, &str), Instances>> = LazyLock::new(DashMap::new);
+
+#[derive(Default)]
+struct Instances {
+    /// The number of instances created so far, which is also the id of the last one.
+    created: u64,
+    live: BTreeSet<u64>,
+}
+
+/// Wraps a value in a [`Tracked`], which logs when it is created and when it is dropped, along
+/// with how many values with the same tag are alive.  The values still alive are written by
+/// [`report()`](crate::report) or when the process exits:
+///
+/// ```text
+/// [dirty-debug] connection: 2 still alive (#1, #4)
+/// ```
+///
+/// This is a poor man's leak detector: tag the values that should go away, and see which ones
+/// do not.  The drop is logged with the location where the value was created.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_leak;
+/// # struct Connection;
+/// #
+/// let connection = ddbg_leak!("/tmp/log", "connection", Connection);
+/// ```
+///
+/// This logs `connection #1 created (1 alive)`, and `connection #1 dropped (0 alive)` once
+/// `connection` is dropped.
+#[macro_export]
+macro_rules! ddbg_leak {
+    ($uri:expr, $tag:literal, $value:expr $(,)?) => {
+        $crate::Tracked::new($uri, ::std::file!(), ::std::line!(), $tag, $value)
+    };
+}
+
+/// A value wrapped by [`ddbg_leak!()`](crate::ddbg_leak), which logs when it is dropped.
+pub struct Tracked<T> {
+    value: T,
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    tag: &'static str,
+    id: u64,
+}
+
+impl<T> Tracked<T> {
+    #[doc(hidden)]
+    pub fn new(
+        uri: &'static str,
+        file: &'static str,
+        line: u32,
+        tag: &'static str,
+        value: T,
+    ) -> Tracked<T> {
+        let (id, alive) = {
+            let mut instances = DIRTY_LEAKS.get_mut(&(uri, tag)).unwrap_or_else(|| {
+                crate::report::report_on_exit();
+                DIRTY_LEAKS.entry((uri, tag)).or_default()
+            });
+
+            instances.created += 1;
+
+            let id = instances.created;
+
+            instances.live.insert(id);
+
+            (id, instances.live.len())
+        };
+
+        crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{tag} #{id} created ({alive} alive)"),
+        );
+
+        Tracked { value, uri, file, line, tag, id }
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
+
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        let Tracked { uri, file, line, tag, id, .. } = *self;
+
+        let alive = DIRTY_LEAKS.get_mut(&(uri, tag)).map_or(0, |mut instances| {
+            instances.live.remove(&id);
+            instances.live.len()
+        });
+
+        crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{tag} #{id} dropped ({alive} alive)"),
+        );
+    }
+}
+
+/// Describes the live instances of a tag, e.g. `2 still alive (#1, #4)`.
+fn describe_live(live: &BTreeSet<u64>) -> String {
+    let mut ids: Vec<String> =
+        live.iter().take(MAX_REPORTED_IDS).map(|id| format!("#{id}")).collect();
+
+    if live.len() > MAX_REPORTED_IDS {
+        ids.push("…".to_owned());
+    }
+
+    format!("{} still alive ({})", live.len(), ids.join(", "))
+}
+
+pub(crate) fn report() {
+    let mut leaks: Vec<(&'static str, &'static str, String)> = DIRTY_LEAKS
+        .iter()
+        .filter(|entry| !entry.value().live.is_empty())
+        .map(|entry| {
+            let (uri, tag) = *entry.key();
+
+            (uri, tag, describe_live(&entry.value().live))
+        })
+        .collect();
+
+    leaks.sort_unstable();
+
+    for (uri, tag, live) in leaks {
+        crate::dirty_log_meta(uri, format_args!("{tag}: {live}"));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_describe_live() {
+        assert_eq!(describe_live(&BTreeSet::from([1, 4])), "2 still alive (#1, #4)");
+        assert_eq!(
+            describe_live(&(1..=20).collect()),
+            "20 still alive (#1, #2, #3, #4, #5, #6, #7, #8, #9, #10, #11, #12, #13, #14, #15, #16, …)"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::{LockResult, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+/// Locks a [`Mutex`], logging when the lock is requested, how long it took to acquire it, and,
+/// when the returned [`LoggedMutexGuard`] is dropped, how long it was held.  This is a drop-in
+/// replacement for `mutex.lock()`, to find out where time goes under lock contention (or where a
+/// deadlock is: a lock that is requested and never acquired).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_lock;
+/// # use std::sync::Mutex;
+/// # let queue: Mutex<Vec<u32>> = Mutex::default();
+/// #
+/// let mut queue = ddbg_lock!("/tmp/log", queue).unwrap();
+///
+/// queue.push(42);
+/// ```
+///
+/// This logs lines like:
+///
+/// ```text
+/// queue: lock requested
+/// queue: locked after 1.21ms
+/// queue: unlocked after 3.50µs
+/// ```
+#[macro_export]
+macro_rules! ddbg_lock {
+    ($uri:expr, $mutex:expr $(,)?) => {
+        $crate::LoggedMutexGuard::lock(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($mutex),
+            &$mutex,
+        )
+    };
+}
+
+/// The guard of a [`ddbg_lock!()`](crate::ddbg_lock), which logs how long the lock was held when
+/// dropped.
+#[must_use = "the mutex is unlocked when the guard is dropped"]
+pub struct LoggedMutexGuard<'a, T: ?Sized> {
+    guard: ManuallyDrop<MutexGuard<'a, T>>,
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &'static str,
+    locked_at: Instant,
+}
+
+impl<'a, T: ?Sized> LoggedMutexGuard<'a, T> {
+    #[doc(hidden)]
+    pub fn lock(
+        uri: &'static str,
+        file: &'static str,
+        line: u32,
+        label: &'static str,
+        mutex: &'a Mutex<T>,
+    ) -> LockResult<LoggedMutexGuard<'a, T>> {
+        crate::dirty_log_message(uri, file, line, format_args!("{label}: lock requested"));
+
+        let requested_at = Instant::now();
+        let result = mutex.lock();
+        let locked_at = Instant::now();
+        let waited = locked_at.saturating_duration_since(requested_at);
+        let poisoned = result.is_err();
+        let guard = result.unwrap_or_else(PoisonError::into_inner);
+
+        if poisoned {
+            crate::dirty_log_message(
+                uri,
+                file,
+                line,
+                format_args!("{label}: locked after {waited:.2?} (poisoned)"),
+            );
+        } else {
+            crate::dirty_log_message(
+                uri,
+                file,
+                line,
+                format_args!("{label}: locked after {waited:.2?}"),
+            );
+        }
+
+        let guard =
+            LoggedMutexGuard { guard: ManuallyDrop::new(guard), uri, file, line, label, locked_at };
+
+        if poisoned {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for LoggedMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: ?Sized> DerefMut for LoggedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for LoggedMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self.guard, f)
+    }
+}
+
+impl<T: ?Sized> Drop for LoggedMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let held = self.locked_at.elapsed();
+
+        // SAFETY: The guard is not used after this.  It is unlocked before logging, so that the
+        // logging does not count as holding the lock.
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+
+        crate::dirty_log_message(
+            self.uri,
+            self.file,
+            self.line,
+            format_args!("{}: unlocked after {held:.2?}", self.label),
+        );
+    }
+}
@@ -0,0 +1,156 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::fmt::Write;
+
+/// Logs a two-dimensional array, like `[[T; N]; M]`, `Vec<Vec<T>>`, or `&[&[T]]`, as an aligned
+/// grid with row and column indices.  Each line of the grid is logged as a message of its own, so
+/// they all keep the `[file:line]` prefix.
+///
+/// The cells are formatted with [`Debug`](std::fmt::Debug), and right-aligned to the same width.
+/// Rows may have different lengths.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_matrix;
+/// let board = [[1, 0, 3], [10, -1, 4]];
+///
+/// ddbg_matrix!("/tmp/log", board);
+/// ```
+///
+/// This logs:
+///
+/// ```text
+/// board: 2×3
+///   |  0  1  2
+/// --+---------
+/// 0 |  1  0  3
+/// 1 | 10 -1  4
+/// ```
+#[macro_export]
+macro_rules! ddbg_matrix {
+    ($uri:expr, $matrix:expr $(,)?) => {{
+        $crate::dirty_log_matrix(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($matrix),
+            &$matrix,
+        );
+    }};
+}
+
+fn digits(n: usize) -> usize {
+    n.checked_ilog10().map_or(1, |d| d as usize + 1)
+}
+
+/// The lines of the grid of the given cells.
+fn grid_lines(rows: &[Vec<String>]) -> Vec<String> {
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let index_width = digits(rows.len().saturating_sub(1));
+    let width = rows
+        .iter()
+        .flatten()
+        .map(|cell| cell.chars().count())
+        .fold(digits(column_count.saturating_sub(1)), usize::max);
+
+    let mut lines = Vec::with_capacity(rows.len() + 2);
+    let mut header = format!("{:index_width$} |", "");
+
+    for c in 0..column_count {
+        let _ = write!(header, " {c:>width$}");
+    }
+
+    lines.push(header);
+    lines.push(format!(
+        "{}+{}",
+        "-".repeat(index_width + 1),
+        "-".repeat(column_count * (width + 1))
+    ));
+
+    for (r, cells) in rows.iter().enumerate() {
+        let mut line = format!("{r:>index_width$} |");
+
+        for cell in cells {
+            let padding = width - cell.chars().count();
+
+            line.push(' ');
+            line.extend(std::iter::repeat(' ').take(padding));
+            line.push_str(cell);
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Logs a `ddbg_matrix!()`.
+#[doc(hidden)]
+pub fn dirty_log_matrix<'a, M, R, T>(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    matrix: &'a M,
+) where
+    &'a M: IntoIterator<Item = R>,
+    R: IntoIterator<Item = T>,
+    T: fmt::Debug,
+{
+    let rows: Vec<Vec<String>> = matrix
+        .into_iter()
+        .map(|row| row.into_iter().map(|cell| format!("{cell:?}")).collect())
+        .collect();
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+    crate::dirty_log_message(
+        uri,
+        file,
+        line,
+        format_args!("{label}: {}×{column_count}", rows.len()),
+    );
+
+    for grid_line in grid_lines(&rows) {
+        crate::dirty_log_message(uri, file, line, format_args!("{grid_line}"));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cells<const N: usize>(rows: &[[&str; N]]) -> Vec<Vec<String>> {
+        rows.iter().map(|row| row.iter().map(|&cell| cell.to_owned()).collect()).collect()
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        assert_eq!(
+            grid_lines(&cells(&[["1", "0", "3"], ["10", "-1", "4"]])),
+            ["  |  0  1  2", "--+---------", "0 |  1  0  3", "1 | 10 -1  4"],
+        );
+    }
+
+    #[test]
+    fn test_grid_lines_wide_indices() {
+        let rows: Vec<Vec<String>> = (0..11).map(|r| vec![r.to_string(); 12]).collect();
+        let lines = grid_lines(&rows);
+
+        assert_eq!(lines[0], "   |  0  1  2  3  4  5  6  7  8  9 10 11");
+        assert_eq!(lines[1], "---+------------------------------------");
+        assert_eq!(lines[2], " 0 |  0  0  0  0  0  0  0  0  0  0  0  0");
+        assert_eq!(lines[12], "10 | 10 10 10 10 10 10 10 10 10 10 10 10");
+    }
+
+    #[test]
+    fn test_grid_lines_ragged() {
+        let rows = vec![vec!["a".to_owned()], vec![], vec!["b".to_owned(), "c".to_owned()]];
+
+        assert_eq!(grid_lines(&rows), ["  | 0 1", "--+----", "0 | a", "1 |", "2 | b c"]);
+    }
+}
@@ -0,0 +1,196 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! What the configuration asks to write at the start of every line, before `[file:line]`.
+
+use crate::config::Timestamp;
+use crate::time::UtcDateTime;
+use std::fmt::Write;
+use std::thread::ThreadId;
+use std::time::Duration;
+
+/// The prefix of a record logged now, which is empty unless the configuration asks for one.
+pub(crate) fn prefix() -> String {
+    let mut prefix = String::new();
+
+    if let Some(timestamp) = crate::config::timestamp() {
+        push_timestamp(&mut prefix, timestamp);
+    }
+
+    if crate::config::pid() {
+        push_process(&mut prefix, crate::process::name(), std::process::id());
+    }
+
+    if crate::config::thread() {
+        let thread = std::thread::current();
+
+        push_thread(&mut prefix, thread.name(), thread.id(), crate::thread::os_thread_id());
+    }
+
+    prefix
+}
+
+fn push_timestamp(prefix: &mut String, timestamp: Timestamp) {
+    match timestamp {
+        Timestamp::Utc => {
+            let _ = write!(prefix, "{} ", UtcDateTime::now());
+        }
+        Timestamp::Uptime => {
+            push_uptime(prefix, crate::time::since_start());
+        }
+    }
+}
+
+/// Appends the process, e.g. `[my-program pid=1234] `.
+fn push_process(prefix: &mut String, name: &str, pid: u32) {
+    let _ = write!(prefix, "[{name} pid={pid}] ");
+}
+
+/// Appends the thread, e.g. `[main tid=4127] `, or `[ThreadId(3) tid=4128] ` if it has no name.
+fn push_thread(prefix: &mut String, name: Option<&str>, id: ThreadId, os_id: Option<u64>) {
+    prefix.push('[');
+
+    match name {
+        Some(name) => prefix.push_str(name),
+        None => {
+            let _ = write!(prefix, "{id:?}");
+        }
+    }
+
+    if let Some(os_id) = os_id {
+        let _ = write!(prefix, " tid={os_id}");
+    }
+
+    prefix.push_str("] ");
+}
+
+/// Appends the time since the previous record written to the same sink, e.g. `(+1.2ms) `.  Nothing
+/// is appended for the first record.
+pub(crate) fn push_delta(prefix: &mut String, delta: Option<Duration>) {
+    if let Some(delta) = delta {
+        let _ = write!(prefix, "(+{delta:.1?}) ");
+    }
+}
+
+fn push_uptime(prefix: &mut String, uptime: Duration) {
+    let _ = write!(prefix, "+{}.{:06}s ", uptime.as_secs(), uptime.subsec_micros());
+}
+
+/// Whether `word` is a timestamp of the prefix, e.g. `2026-10-16T12:34:56.123456Z` or `+1.000000s`.
+fn is_timestamp(word: &str) -> bool {
+    (word.ends_with('Z') && word.as_bytes().get(10) == Some(&b'T'))
+        || (word.starts_with('+') && word.ends_with('s'))
+}
+
+/// Skips the prefix of a logged line (whatever the configuration that wrote it), so that the line
+/// starts with `[file:line]` or `[dirty-debug]`.  Lines that don't have them, like the
+/// continuation lines of a multiline message, are returned as they are.  This is for the tools
+/// that read logs.
+#[doc(hidden)]
+#[must_use]
+pub fn dirty_skip_prefix(line: &str) -> &str {
+    let mut rest = line;
+
+    loop {
+        if let Some((group, after)) = rest.strip_prefix('[').and_then(|r| r.split_once("] ")) {
+            let is_call_site = group.rsplit_once(':').is_some_and(|(_, line)| {
+                !line.is_empty() && line.bytes().all(|b| b.is_ascii_digit())
+            });
+
+            if group == "dirty-debug" || is_call_site {
+                return rest;
+            }
+
+            // The process or the thread.
+            rest = after;
+            continue;
+        }
+
+        match rest.split_once(' ') {
+            Some((word, after))
+                if is_timestamp(word) || (word.starts_with("(+") && word.ends_with(')')) =>
+            {
+                rest = after;
+            }
+            _ => return line,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push_timestamp_utc() {
+        let mut prefix = String::new();
+
+        push_timestamp(&mut prefix, Timestamp::Utc);
+
+        assert_eq!(prefix.len(), "2026-10-16T12:34:56.123456Z ".len(), "{prefix}");
+        assert!(prefix.ends_with("Z "), "{prefix}");
+        assert_eq!(prefix.as_bytes()[10], b'T', "{prefix}");
+    }
+
+    #[test]
+    fn test_push_uptime() {
+        let mut prefix = String::new();
+
+        push_uptime(&mut prefix, Duration::new(12, 345_678_901));
+        push_uptime(&mut prefix, Duration::from_micros(1));
+
+        assert_eq!(prefix, "+12.345678s +0.000001s ");
+    }
+
+    #[test]
+    fn test_push_delta() {
+        let mut prefix = String::from("+1.000000s ");
+
+        push_delta(&mut prefix, None);
+        push_delta(&mut prefix, Some(Duration::from_micros(1234)));
+        push_delta(&mut prefix, Some(Duration::from_secs(2)));
+
+        assert_eq!(prefix, "+1.000000s (+1.2ms) (+2.0s) ");
+    }
+
+    #[test]
+    fn test_push_thread() {
+        let id = std::thread::current().id();
+        let mut prefix = String::new();
+
+        push_thread(&mut prefix, Some("main"), id, Some(4127));
+        push_thread(&mut prefix, Some("worker"), id, None);
+        push_thread(&mut prefix, None, id, Some(4128));
+
+        assert_eq!(prefix, format!("[main tid=4127] [worker] [{id:?} tid=4128] "));
+    }
+
+    #[test]
+    fn test_dirty_skip_prefix() {
+        assert_eq!(dirty_skip_prefix("[src/main.rs:7] hello"), "[src/main.rs:7] hello");
+        assert_eq!(dirty_skip_prefix("[dirty-debug] opening"), "[dirty-debug] opening");
+        assert_eq!(
+            dirty_skip_prefix("2026-10-16T12:34:56.123456Z [src/main.rs:7] hello"),
+            "[src/main.rs:7] hello",
+        );
+        assert_eq!(
+            dirty_skip_prefix("+1.000000s [server pid=42] [main tid=7] (+1.2ms) [a.rs:1] x"),
+            "[a.rs:1] x",
+        );
+        assert_eq!(dirty_skip_prefix("[worker] [dirty-debug] bye"), "[dirty-debug] bye");
+        assert_eq!(dirty_skip_prefix("  field: 3,"), "  field: 3,");
+        assert_eq!(dirty_skip_prefix("[1, 2] and more"), "[1, 2] and more");
+        assert_eq!(dirty_skip_prefix(""), "");
+    }
+
+    #[test]
+    fn test_push_process() {
+        let mut prefix = String::new();
+
+        push_process(&mut prefix, "my-program", 1234);
+
+        assert_eq!(prefix, "[my-program pid=1234] ");
+    }
+}
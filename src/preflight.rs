@@ -0,0 +1,51 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::io;
+
+/// The outcome of opening a destination with [`preflight()`].
+#[derive(Debug)]
+pub struct PreflightResult {
+    /// The uri of the destination.
+    pub uri: &'static str,
+    /// Why the destination could not be opened, if it could not.
+    pub error: Option<io::Error>,
+}
+
+impl PreflightResult {
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Opens (or connects to) every destination in `uris` right away, instead of on the first message
+/// logged to it, so that you find out that a destination is unreachable before a long run and not
+/// after.  Every failure is also written to `stderr`.
+///
+/// The destinations stay open, so the messages you log afterwards go through the same file or
+/// connection.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let results = dirty_debug::preflight(&["tcp://debug-host:12345", "/tmp/log"]);
+///
+/// assert!(results.iter().all(|r| r.is_ok()));
+/// ```
+#[must_use]
+pub fn preflight(uris: &[&'static str]) -> Vec<PreflightResult> {
+    uris.iter()
+        .map(|&uri| {
+            let error = crate::sink::open_cached(uri).err();
+
+            if let Some(e) = &error {
+                eprintln!("dirty-debug: preflight: cannot open \"{uri}\": {e}");
+            }
+
+            PreflightResult { uri, error }
+        })
+        .collect()
+}
@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::sync::LazyLock;
+use std::time::SystemTime;
+
+static PROCESS_NAME: LazyLock<String> = LazyLock::new(|| {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "unknown".to_owned())
+});
+
+/// The name of the current process' executable.
+pub(crate) fn name() -> &'static str {
+    PROCESS_NAME.as_str()
+}
+
+static EXECUTABLE_FINGERPRINT: LazyLock<String> = LazyLock::new(|| {
+    let metadata = std::env::current_exe().and_then(std::fs::metadata);
+    let modified = metadata.as_ref().ok().and_then(|m| m.modified().ok());
+
+    let (Ok(metadata), Some(modified)) = (&metadata, modified) else {
+        return "unknown".to_owned();
+    };
+
+    let modified = modified.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+
+    // FNV-1a, since we need the same hash in every run.
+    let hash = [metadata.len(), modified.as_secs(), u64::from(modified.subsec_nanos())]
+        .iter()
+        .flat_map(|n| n.to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+    format!("{hash:016x}")
+});
+
+static HOSTNAME: LazyLock<String> = LazyLock::new(|| {
+    #[cfg(unix)]
+    {
+        let mut buffer: [u8; 256] = [0; 256];
+
+        // SAFETY: The buffer is valid for its whole length.
+        let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+
+        if result == 0 {
+            let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+
+            return String::from_utf8_lossy(&buffer[..len]).into_owned();
+        }
+    }
+
+    #[cfg(windows)]
+    if let Ok(hostname) = std::env::var("COMPUTERNAME") {
+        return hostname;
+    }
+
+    "unknown".to_owned()
+});
+
+/// A hash of the size and modification time of the current process' executable, which we use to
+/// tell builds apart.  This is a heuristic: rebuilding (or just touching) the executable changes
+/// the fingerprint even if the code is the same, and copying it elsewhere may or may not keep the
+/// modification time.  It is only meant to tell whether two runs probably ran the same binary.
+pub(crate) fn executable_fingerprint() -> &'static str {
+    EXECUTABLE_FINGERPRINT.as_str()
+}
+
+/// The name of the host we are running on.
+pub(crate) fn hostname() -> &'static str {
+    HOSTNAME.as_str()
+}
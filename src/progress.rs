@@ -0,0 +1,139 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Logs the progress of a loop, with an estimate of the time left.  To keep the log short, it is
+/// logged at most once per second and once per percent, besides the first and the last step.
+///
+/// `done` is the number of items completed so far, out of `total`.  When it goes back down (e.g.
+/// the loop runs again) the progress starts over.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_progress;
+/// # let files = vec!["a"; 1000];
+/// # let process = |_: &&str| ();
+/// #
+/// for (i, file) in files.iter().enumerate() {
+///     process(file);
+///     ddbg_progress!("/tmp/log", i + 1, files.len());
+/// }
+/// ```
+///
+/// This logs lines like `processed 500/1000 (50%, ETA 12s)`.
+#[macro_export]
+macro_rules! ddbg_progress {
+    ($uri:expr, $done:expr, $total:expr $(,)?) => {{
+        static PROGRESS: $crate::Progress = $crate::Progress::new();
+
+        PROGRESS.step(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            u64::try_from($done).unwrap_or(0),
+            u64::try_from($total).unwrap_or(0),
+        );
+    }};
+}
+
+struct Run {
+    start: Instant,
+    done: u64,
+    /// When we last logged, and the percentage we logged.
+    logged: (Instant, u64),
+}
+
+fn percent(done: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 100;
+    }
+
+    u64::try_from(u128::from(done) * 100 / u128::from(total)).unwrap_or(100)
+}
+
+fn summary(done: u64, total: u64, elapsed: Duration) -> String {
+    let percent = percent(done, total);
+
+    if done >= total {
+        return format!("processed {done}/{total} ({percent}%, took {elapsed:.1?})");
+    }
+
+    let eta = if done == 0 {
+        "?".to_owned()
+    } else {
+        let left_ms = elapsed.as_millis() * u128::from(total - done) / u128::from(done);
+
+        format!("{}s", (left_ms + 500) / 1000)
+    };
+
+    format!("processed {done}/{total} ({percent}%, ETA {eta})")
+}
+
+/// The state of a [`ddbg_progress!()`](crate::ddbg_progress) call site.
+#[doc(hidden)]
+pub struct Progress {
+    run: Mutex<Option<Run>>,
+}
+
+impl Progress {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Progress {
+        Progress { run: Mutex::new(None) }
+    }
+
+    pub fn step(&self, uri: &'static str, file: &'static str, line: u32, done: u64, total: u64) {
+        let now = Instant::now();
+
+        let summary = {
+            let mut run = self.run.lock().unwrap_or_else(PoisonError::into_inner);
+
+            match &mut *run {
+                Some(run) if done >= run.done => {
+                    let percent = percent(done, total);
+                    let finished = done >= total && run.done < total;
+                    let due = now - run.logged.0 >= MIN_INTERVAL && percent > run.logged.1;
+
+                    run.done = done;
+
+                    (finished || due).then(|| {
+                        run.logged = (now, percent);
+                        summary(done, total, now - run.start)
+                    })
+                }
+                _ => {
+                    *run = Some(Run { start: now, done, logged: (now, percent(done, total)) });
+
+                    Some(summary(done, total, Duration::ZERO))
+                }
+            }
+        };
+
+        if let Some(summary) = summary {
+            crate::dirty_log_message(uri, file, line, format_args!("{summary}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let second = Duration::from_secs(1);
+
+        assert_eq!(summary(0, 1000, Duration::ZERO), "processed 0/1000 (0%, ETA ?)");
+        assert_eq!(summary(250, 1000, 10 * second), "processed 250/1000 (25%, ETA 30s)");
+        assert_eq!(summary(999, 1000, 999 * second), "processed 999/1000 (99%, ETA 1s)");
+        assert_eq!(summary(1000, 1000, 12 * second), "processed 1000/1000 (100%, took 12.0s)");
+        assert_eq!(summary(0, 0, Duration::ZERO), "processed 0/0 (100%, took 0.0ns)");
+    }
+}
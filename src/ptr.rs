@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+/// Logs a pointer, or the address of a reference, together with the memory mapping it falls into
+/// (e.g. the heap, a thread's stack, or which shared object), so that a bare address tells you
+/// something.  The mappings are only known on Linux and Android, where they are read from
+/// `/proc/self/maps`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_ptr;
+/// # let buffer: Vec<u8> = vec![0; 16];
+/// #
+/// ddbg_ptr!("/tmp/log", buffer.as_ptr());
+/// ddbg_ptr!("/tmp/log", &buffer);
+/// ```
+///
+/// This logs lines like `buffer.as_ptr() = 0x5581f0a3c2b0 (rw-p [heap] +0x2b0)` or
+/// `&buffer = 0x7ffc8e1e4a10 (rw-p [stack] +0x1fa10)`.  For a file mapping, the offset is in the
+/// file.
+#[macro_export]
+macro_rules! ddbg_ptr {
+    ($uri:expr, $ptr:expr $(,)?) => {{
+        $crate::dirty_log_ptr($uri, ::std::file!(), ::std::line!(), ::std::stringify!($ptr), $ptr);
+    }};
+}
+
+/// A line of `/proc/self/maps`.
+#[cfg_attr(not(any(target_os = "linux", target_os = "android", test)), allow(dead_code))]
+#[derive(PartialEq, Eq, Debug)]
+struct Mapping<'a> {
+    start: usize,
+    end: usize,
+    permissions: &'a str,
+    offset: usize,
+    pathname: &'a str,
+}
+
+#[cfg_attr(not(any(target_os = "linux", target_os = "android", test)), allow(dead_code))]
+fn parse_mapping(line: &str) -> Option<Mapping<'_>> {
+    let mut fields = line.splitn(6, ' ');
+    let (start, end) = fields.next()?.split_once('-')?;
+    let permissions = fields.next()?;
+    let offset = fields.next()?;
+    let pathname = fields.nth(2).unwrap_or("").trim();
+
+    Some(Mapping {
+        start: usize::from_str_radix(start, 16).ok()?,
+        end: usize::from_str_radix(end, 16).ok()?,
+        permissions,
+        offset: usize::from_str_radix(offset, 16).ok()?,
+        pathname,
+    })
+}
+
+/// Describes the mapping of `address` in the given contents of `/proc/self/maps`.
+#[cfg_attr(not(any(target_os = "linux", target_os = "android", test)), allow(dead_code))]
+fn describe(maps: &str, address: usize) -> String {
+    let mapping = maps
+        .lines()
+        .filter_map(parse_mapping)
+        .find(|mapping| (mapping.start..mapping.end).contains(&address));
+
+    match mapping {
+        None => "not mapped".to_owned(),
+        Some(Mapping { start, permissions, offset, pathname, .. }) => {
+            let pathname = if pathname.is_empty() { "anonymous" } else { pathname };
+
+            format!("{permissions} {pathname} +{:#x}", address - start + offset)
+        }
+    }
+}
+
+/// Logs a `ddbg_ptr!()`.
+#[doc(hidden)]
+pub fn dirty_log_ptr<T: ?Sized>(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    ptr: *const T,
+) {
+    let address = ptr.cast::<()>() as usize;
+
+    if address == 0 {
+        crate::dirty_log_message(uri, file, line, format_args!("{label} = null"));
+        return;
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let Ok(maps) = std::fs::read_to_string("/proc/self/maps") {
+        let mapping = describe(&maps, address);
+
+        crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label} = {address:#x} ({mapping})"),
+        );
+        return;
+    }
+
+    crate::dirty_log_message(uri, file, line, format_args!("{label} = {address:#x}"));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAPS: &str = "\
+55d0e4c3e000-55d0e4c40000 r--p 00000000 08:01 1311 /usr/bin/cat
+55d0e4c40000-55d0e4c45000 r-xp 00002000 08:01 1311 /usr/bin/cat
+55d0e5a5c000-55d0e5a7d000 rw-p 00000000 00:00 0                          [heap]
+7f1c1a000000-7f1c1a021000 rw-p 00000000 00:00 0 
+7ffd4b1d0000-7ffd4b1f1000 rw-p 00000000 00:00 0                          [stack]
+";
+
+    #[test]
+    fn test_parse_mapping() {
+        assert_eq!(
+            parse_mapping("55d0e4c40000-55d0e4c45000 r-xp 00002000 08:01 1311 /usr/bin/my cat"),
+            Some(Mapping {
+                start: 0x55d0_e4c4_0000,
+                end: 0x55d0_e4c4_5000,
+                permissions: "r-xp",
+                offset: 0x2000,
+                pathname: "/usr/bin/my cat",
+            })
+        );
+        assert_eq!(parse_mapping("garbage"), None);
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(MAPS, 0x55d0_e4c4_0010), "r-xp /usr/bin/cat +0x2010");
+        assert_eq!(describe(MAPS, 0x55d0_e5a5_c2b0), "rw-p [heap] +0x2b0");
+        assert_eq!(describe(MAPS, 0x7f1c_1a00_0008), "rw-p anonymous +0x8");
+        assert_eq!(describe(MAPS, 0x7ffd_4b1e_fa10), "rw-p [stack] +0x1fa10");
+        assert_eq!(describe(MAPS, 0x1000), "not mapped");
+    }
+}
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::cell::RefCell;
+
+/// A quiet phase of the current thread.
+struct Quiet {
+    label: String,
+    /// How many messages were not logged, per destination, in the order we first saw them.
+    suppressed: Vec<(&'static str, u64)>,
+}
+
+thread_local! {
+    static QUIET: RefCell<Option<Quiet>> = const { RefCell::new(None) };
+}
+
+/// Ends a quiet phase, even if the closure panics, and logs its summary.
+struct QuietGuard {
+    previous: Option<Quiet>,
+}
+
+impl Drop for QuietGuard {
+    fn drop(&mut self) {
+        let Some(quiet) = QUIET.with(|q| q.replace(self.previous.take())) else {
+            return;
+        };
+
+        for (uri, count) in quiet.suppressed {
+            crate::dirty_log_meta(
+                uri,
+                format_args!("quiet \"{}\": {count} messages not logged", quiet.label),
+            );
+        }
+    }
+}
+
+/// Runs `f` without logging the messages it logs, and then logs how many messages were not
+/// logged to each destination, e.g. `quiet "startup": 1234 messages not logged`.  This is useful
+/// to skip noisy phases of your program you don't care about:
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg;
+/// # fn initialize() {}
+/// #
+/// dirty_debug::quiet_during("startup", || initialize());
+///
+/// ddbg!("/tmp/log", "This is the interesting part");
+/// ```
+///
+/// Only the messages of the current thread are silenced (and not even formatted).  Errors, such as
+/// those of [`ddbg_err!()`](crate::ddbg_err), [`ddbg_assert!()`](crate::ddbg_assert) and
+/// [`ddbg_fatal!()`](crate::ddbg_fatal), and messages of dirty-debug itself, such as the summary of
+/// [`ddbg_check!()`](crate::ddbg_check), are still logged.
+pub fn quiet_during<R>(label: &str, f: impl FnOnce() -> R) -> R {
+    let quiet = Quiet { label: label.to_owned(), suppressed: Vec::new() };
+    let _guard = QuietGuard { previous: QUIET.with(|q| q.replace(Some(quiet))) };
+
+    f()
+}
+
+/// If the current thread is in a quiet phase, counts a message to `uri` and returns `true`.
+pub(crate) fn suppress(uri: &'static str) -> bool {
+    QUIET.with(|q| {
+        let mut quiet = q.borrow_mut();
+
+        let Some(quiet) = quiet.as_mut() else {
+            return false;
+        };
+
+        match quiet.suppressed.iter_mut().find(|(u, _)| *u == uri) {
+            Some((_, count)) => *count += 1,
+            None => quiet.suppressed.push((uri, 1)),
+        }
+
+        true
+    })
+}
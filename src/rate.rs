@@ -0,0 +1,155 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt::Write;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Measures how often this call site is executed.  About once a second it logs the number of calls
+/// per second, and the shortest and longest time between two consecutive calls.
+///
+/// Nothing runs in the background: the measurement of a second is logged by the first call after
+/// it.  If the gaps are long, the measurement covers more than a second.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_rate;
+/// #
+/// loop {
+///     ddbg_rate!("/tmp/log", "packets");
+/// #   break;
+/// }
+/// ```
+///
+/// This logs lines like `packets: 1523.4 calls/s (1524 calls in 1.00s, gaps 102.31µs..9.80ms)`.
+#[macro_export]
+macro_rules! ddbg_rate {
+    ($uri:expr, $label:expr $(,)?) => {{
+        static RATE: $crate::Rate = $crate::Rate::new();
+
+        RATE.hit($uri, ::std::file!(), ::std::line!(), $label);
+    }};
+}
+
+struct Window {
+    start: Instant,
+    /// The calls after `start`, which is itself the time of a call.
+    calls: u64,
+    min_gap: Duration,
+    max_gap: Duration,
+}
+
+impl Window {
+    fn new(start: Instant) -> Window {
+        Window { start, calls: 0, min_gap: Duration::MAX, max_gap: Duration::ZERO }
+    }
+
+    fn summary(&self, label: &str, elapsed: Duration) -> String {
+        let rate = f64::from(u32::try_from(self.calls).unwrap_or(u32::MAX)) / elapsed.as_secs_f64();
+        let mut summary =
+            format!("{label}: {rate:.1} calls/s ({} calls in {elapsed:.2?}", self.calls);
+
+        if self.min_gap <= self.max_gap {
+            let _ = write!(summary, ", gaps {:.2?}..{:.2?}", self.min_gap, self.max_gap);
+        }
+
+        summary.push(')');
+        summary
+    }
+}
+
+/// The state of a [`ddbg_rate!()`](crate::ddbg_rate) call site.
+#[doc(hidden)]
+pub struct Rate {
+    /// The current window and the time of the last call.
+    state: Mutex<Option<(Window, Instant)>>,
+}
+
+impl Rate {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Rate {
+        Rate { state: Mutex::new(None) }
+    }
+
+    pub fn hit(&self, uri: &'static str, file: &'static str, line: u32, label: &str) {
+        if let Some(summary) = self.hit_at(Instant::now(), label) {
+            crate::dirty_log_message(uri, file, line, format_args!("{summary}"));
+        }
+    }
+
+    /// Records a call at `now`, returning the summary of the window it closes, if any.
+    fn hit_at(&self, now: Instant, label: &str) -> Option<String> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let Some((window, last_call)) = &mut *state else {
+            *state = Some((Window::new(now), now));
+            return None;
+        };
+
+        let gap = now - *last_call;
+
+        window.calls += 1;
+        window.min_gap = window.min_gap.min(gap);
+        window.max_gap = window.max_gap.max(gap);
+        *last_call = now;
+
+        let elapsed = now - window.start;
+
+        (elapsed >= WINDOW).then(|| {
+            let summary = window.summary(label, elapsed);
+
+            // The call that closes this window starts the next one.
+            *window = Window::new(now);
+            summary
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_window_summary() {
+        let mut window = Window::new(Instant::now());
+
+        window.calls = 3;
+
+        assert_eq!(
+            window.summary("x", Duration::from_secs(2)),
+            "x: 1.5 calls/s (3 calls in 2.00s)"
+        );
+
+        window.min_gap = Duration::from_millis(1);
+        window.max_gap = Duration::from_millis(250);
+
+        assert_eq!(
+            window.summary("x", Duration::from_millis(1500)),
+            "x: 2.0 calls/s (3 calls in 1.50s, gaps 1.00ms..250.00ms)",
+        );
+    }
+
+    #[test]
+    fn test_rate_windows() {
+        let rate = Rate::new();
+        let start = Instant::now();
+        let summaries: Vec<String> = (0..=200)
+            .filter_map(|i| rate.hit_at(start + Duration::from_millis(i * 10), "x"))
+            .collect();
+
+        // Every window counts the calls the same way, the first one included.
+        assert_eq!(
+            summaries,
+            [
+                "x: 100.0 calls/s (100 calls in 1.00s, gaps 10.00ms..10.00ms)",
+                "x: 100.0 calls/s (100 calls in 1.00s, gaps 10.00ms..10.00ms)",
+            ],
+        );
+    }
+}
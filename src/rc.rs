@@ -0,0 +1,114 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Logs the strong and weak counts of an [`Rc`], an [`Arc`], or one of their `Weak` pointers,
+/// along with the address of the shared value, to find out why it is never freed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_rc;
+/// # use std::sync::Arc;
+/// # let config = Arc::new(());
+/// #
+/// ddbg_rc!("/tmp/log", config);
+/// ```
+///
+/// This logs lines like `config: 3 strong, 1 weak (at 0x5581f0a3c2b0)`.
+#[macro_export]
+macro_rules! ddbg_rc {
+    ($uri:expr, $rc:expr $(,)?) => {{
+        $crate::dirty_log_rc($uri, ::std::file!(), ::std::line!(), ::std::stringify!($rc), &$rc);
+    }};
+}
+
+/// A reference-counted pointer.
+#[doc(hidden)]
+pub trait RefCounted {
+    fn strong_count(&self) -> usize;
+    fn weak_count(&self) -> usize;
+    fn address(&self) -> *const ();
+}
+
+impl<T: ?Sized> RefCounted for Rc<T> {
+    fn strong_count(&self) -> usize {
+        Rc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Rc::weak_count(self)
+    }
+
+    fn address(&self) -> *const () {
+        Rc::as_ptr(self).cast()
+    }
+}
+
+impl<T: ?Sized> RefCounted for std::rc::Weak<T> {
+    fn strong_count(&self) -> usize {
+        std::rc::Weak::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        std::rc::Weak::weak_count(self)
+    }
+
+    fn address(&self) -> *const () {
+        std::rc::Weak::as_ptr(self).cast()
+    }
+}
+
+impl<T: ?Sized> RefCounted for Arc<T> {
+    fn strong_count(&self) -> usize {
+        Arc::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        Arc::weak_count(self)
+    }
+
+    fn address(&self) -> *const () {
+        Arc::as_ptr(self).cast()
+    }
+}
+
+impl<T: ?Sized> RefCounted for std::sync::Weak<T> {
+    fn strong_count(&self) -> usize {
+        std::sync::Weak::strong_count(self)
+    }
+
+    fn weak_count(&self) -> usize {
+        std::sync::Weak::weak_count(self)
+    }
+
+    fn address(&self) -> *const () {
+        std::sync::Weak::as_ptr(self).cast()
+    }
+}
+
+/// Logs a `ddbg_rc!()`.
+#[doc(hidden)]
+pub fn dirty_log_rc(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    rc: &impl RefCounted,
+) {
+    crate::dirty_log_message(
+        uri,
+        file,
+        line,
+        format_args!(
+            "{label}: {} strong, {} weak (at {:p})",
+            rc.strong_count(),
+            rc.weak_count(),
+            rc.address(),
+        ),
+    );
+}
@@ -0,0 +1,36 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+
+/// The source code location that logged a record.
+#[derive(Clone, Copy)]
+pub(crate) struct Location {
+    pub(crate) file: &'static str,
+    pub(crate) line: u32,
+}
+
+/// A formatted message together with the source code location that logged it.  Messages written
+/// by `dirty-debug` itself (e.g. summaries) have no location.
+pub(crate) struct Record<'a> {
+    pub(crate) location: Option<Location>,
+    pub(crate) message: &'a str,
+    /// What the configuration asks to write before the location, e.g. the time (see
+    /// [`crate::prefix`]).  Sinks with fields of their own for that kind of data ignore it.
+    pub(crate) prefix: &'a str,
+}
+
+/// Formats the record as `[file:line] message` (or `[dirty-debug] message` if it has no location),
+/// after the prefix, which is how it is written by text-based sinks.
+impl fmt::Display for Record<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.prefix)?;
+
+        match self.location {
+            Some(Location { file, line }) => write!(f, "[{file}:{line}] {}", self.message),
+            None => write!(f, "[dirty-debug] {}", self.message),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+/// Writes a summary of the statistics aggregated so far (e.g. by
+/// [`ddbg_check!()`](crate::ddbg_check), [`ddbg_count!()`](crate::ddbg_count),
+/// [`ddbg_hist!()`](crate::ddbg_hist), the values of [`ddbg_leak!()`](crate::ddbg_leak) still
+/// alive, or the messages not logged because of `DIRTY_DEBUG_MAX_OUTPUT`) to their respective
+/// destinations.
+///
+/// This is also done automatically when the process exits.
+pub fn report() {
+    crate::check::report();
+    crate::count::report();
+    crate::hist::report();
+    crate::leak::report();
+    crate::cap::report();
+}
+
+/// Makes sure the statistics are reported when the process exits.
+pub(crate) fn report_on_exit() {
+    crate::exit::on_exit(report);
+}
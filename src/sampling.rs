@@ -0,0 +1,153 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Like [`ddbg!()`](crate::ddbg), but it only logs the first time this call site is executed.
+/// The message is not even formatted after that, so this is cheap to leave in a hot loop.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_once;
+/// #
+/// for packet in 0..1_000_000 {
+///     ddbg_once!("/tmp/log", "first packet: {}", packet);
+/// }
+/// ```
+#[macro_export]
+macro_rules! ddbg_once {
+    ($uri:expr, $($arg:tt)+) => {{
+        static LOGGED: ::std::sync::atomic::AtomicBool = ::std::sync::atomic::AtomicBool::new(false);
+
+        if !LOGGED.swap(true, ::std::sync::atomic::Ordering::Relaxed) {
+            $crate::ddbg!($uri, $($arg)+);
+        }
+    }};
+}
+
+/// Like [`ddbg!()`](crate::ddbg), but it only logs when `cond` is true.  The message is not
+/// formatted otherwise, and if `cond` is a constant that is false the whole call compiles away.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_if;
+/// # let (balance, account) = (-1, 7);
+/// #
+/// const TRACE_ACCOUNTS: bool = true;
+///
+/// ddbg_if!(balance < 0, "/tmp/log", "negative balance in account {}", account);
+/// ddbg_if!(TRACE_ACCOUNTS, "/tmp/log", "balance of account {}: {}", account, balance);
+/// ```
+#[macro_export]
+macro_rules! ddbg_if {
+    ($cond:expr, $uri:expr, $($arg:tt)+) => {{
+        if $cond {
+            $crate::ddbg!($uri, $($arg)+);
+        }
+    }};
+}
+
+/// Like [`ddbg!()`](crate::ddbg), but it only logs every `n` executions of this call site (the
+/// first, the `n + 1`-th, and so on), adding how many times the call site was executed to the
+/// message.  The message is not formatted when it is not logged.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_every;
+/// #
+/// for frame in 0..1_000_000 {
+///     ddbg_every!(1000, "/tmp/log", "tick {}", frame);
+/// }
+/// ```
+///
+/// This logs `tick 0 (hit 1)`, `tick 1000 (hit 1001)`, `tick 2000 (hit 2001)`, etc.
+#[macro_export]
+macro_rules! ddbg_every {
+    ($n:expr, $uri:expr, $($arg:tt)+) => {{
+        static HITS: ::std::sync::atomic::AtomicU64 = ::std::sync::atomic::AtomicU64::new(0);
+
+        let hit: u64 = HITS.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) + 1;
+        let n: u64 = $n;
+
+        if (hit - 1) % n.max(1) == 0 {
+            $crate::dirty_log_message(
+                $uri,
+                ::std::file!(),
+                ::std::line!(),
+                ::std::format_args!("{} (hit {hit})", ::std::format_args!($($arg)+)),
+            );
+        }
+    }};
+}
+
+/// Like [`ddbg!()`](crate::ddbg), but this call site logs at most once per `interval`.  When
+/// messages were skipped since the last one logged, how many is added to the message.  The
+/// message is not formatted when it is not logged.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_throttle;
+/// # use std::time::Duration;
+/// # let queue_len = 0;
+/// #
+/// loop {
+///     ddbg_throttle!(Duration::from_secs(1), "/tmp/log", "queue length: {}", queue_len);
+/// #   break;
+/// }
+/// ```
+///
+/// This logs lines like `queue length: 42 (suppressed 183204)`.
+#[macro_export]
+macro_rules! ddbg_throttle {
+    ($interval:expr, $uri:expr, $($arg:tt)+) => {{
+        static THROTTLE: $crate::Throttle = $crate::Throttle::new();
+
+        match THROTTLE.admit($interval) {
+            None => (),
+            Some(0) => $crate::ddbg!($uri, $($arg)+),
+            Some(suppressed) => $crate::dirty_log_message(
+                $uri,
+                ::std::file!(),
+                ::std::line!(),
+                ::std::format_args!("{} (suppressed {suppressed})", ::std::format_args!($($arg)+)),
+            ),
+        }
+    }};
+}
+
+/// The state of a [`ddbg_throttle!()`](crate::ddbg_throttle) call site.
+#[doc(hidden)]
+pub struct Throttle {
+    /// When the last message was logged, and how many were suppressed since then.
+    state: Mutex<(Option<Instant>, u64)>,
+}
+
+impl Throttle {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Throttle {
+        Throttle { state: Mutex::new((None, 0)) }
+    }
+
+    /// Returns how many messages were suppressed if a message should be logged now.
+    pub fn admit(&self, interval: Duration) -> Option<u64> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (last_logged, suppressed) = &mut *state;
+
+        if last_logged.is_some_and(|last_logged| last_logged.elapsed() < interval) {
+            *suppressed += 1;
+            return None;
+        }
+
+        *last_logged = Some(Instant::now());
+
+        Some(std::mem::take(suppressed))
+    }
+}
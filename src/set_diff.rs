@@ -0,0 +1,80 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+/// Logs which keys were added and removed since the previous time this call site was executed.
+/// Nothing is logged if the set of keys did not change.
+///
+/// The keys are compared by their [`Debug`](std::fmt::Debug) representation.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_set_diff;
+/// # let sessions: std::collections::HashMap<u32, ()> = std::collections::HashMap::new();
+/// #
+/// ddbg_set_diff!("/tmp/log", "sessions", sessions.keys());
+/// ```
+///
+/// This will log lines like `sessions: +[7, 9] -[3]`.
+#[macro_export]
+macro_rules! ddbg_set_diff {
+    ($uri:expr, $label:expr, $keys:expr $(,)?) => {{
+        static SET_DIFF: $crate::SetDiff = $crate::SetDiff::new();
+
+        SET_DIFF.log($uri, ::std::file!(), ::std::line!(), $label, $keys);
+    }};
+}
+
+/// The state of a [`ddbg_set_diff!()`](crate::ddbg_set_diff) call site.
+#[doc(hidden)]
+pub struct SetDiff {
+    previous: Mutex<BTreeSet<String>>,
+}
+
+impl SetDiff {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> SetDiff {
+        SetDiff { previous: Mutex::new(BTreeSet::new()) }
+    }
+
+    pub fn log<K: fmt::Debug>(
+        &self,
+        uri: &'static str,
+        file: &'static str,
+        line: u32,
+        label: &str,
+        keys: impl IntoIterator<Item = K>,
+    ) {
+        let current: BTreeSet<String> = keys.into_iter().map(|key| format!("{key:?}")).collect();
+
+        let (added, removed) = {
+            let mut previous = self.previous.lock().unwrap_or_else(PoisonError::into_inner);
+
+            let added: Vec<String> = current.difference(&previous).cloned().collect();
+            let removed: Vec<String> = previous.difference(&current).cloned().collect();
+
+            *previous = current;
+
+            (added, removed)
+        };
+
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label}: +[{}] -[{}]", added.join(", "), removed.join(", ")),
+        );
+    }
+}
@@ -0,0 +1,169 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::Sink;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Tears down every destination, taking at most `timeout`: the statistics are reported (see
+/// [`report()`](crate::report)), then the destinations are flushed (e.g. the pending batch of an
+/// http destination is posted) and closed, all at the same time.  Destinations that did not
+/// finish in time are reported in `stderr` and left to finish in the background.
+///
+/// This is meant for code that needs a bounded teardown, like a plugin that is about to be
+/// unloaded.  Nothing is done when the process exits after this is called, and logging to a
+/// destination afterwards opens it again.
+///
+/// The timeout only bounds a teardown that cooperates: the threads of destinations that finish in
+/// time (e.g. the one posting the batches of `http://`) are stopped and joined, but the report or
+/// a destination that did not finish in time (e.g. because a server does not answer) keeps running
+/// crate code in the background after this returns.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// #
+/// dirty_debug::shutdown_with_timeout(Duration::from_secs(2));
+/// ```
+pub fn shutdown_with_timeout(timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+
+    if !run_until(crate::report::report, deadline) {
+        eprintln!("dirty-debug: the report did not finish within {timeout:?}");
+    }
+
+    crate::exit::forget_callbacks();
+
+    for uri in close_until(crate::sink::take_all(), deadline) {
+        eprintln!("dirty-debug: \"{uri}\" did not shut down within {timeout:?}");
+    }
+}
+
+/// Runs `f` in its own thread, returning whether it finished by `deadline`.  If it did not, it is
+/// left to finish in the background.
+fn run_until(f: impl FnOnce() + Send + 'static, deadline: Instant) -> bool {
+    let (sender, receiver) = mpsc::channel::<()>();
+
+    let spawned =
+        std::thread::Builder::new().name("dirty-debug-shutdown".to_owned()).spawn(move || {
+            f();
+
+            let _ = sender.send(());
+        });
+
+    if let Err(e) = spawned {
+        eprintln!("dirty-debug: failed to spawn thread: {e}");
+        return false;
+    }
+
+    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())).is_ok()
+}
+
+/// Flushes and drops every sink, each in its own thread, returning the uris of the ones that did
+/// not finish by `deadline`.
+fn close_until(sinks: Vec<(&'static str, Box<dyn Sink>)>, deadline: Instant) -> Vec<&'static str> {
+    let (sender, receiver) = mpsc::channel();
+    let mut pending: Vec<&'static str> = Vec::new();
+
+    for (uri, mut sink) in sinks {
+        let sender = sender.clone();
+
+        let spawned =
+            std::thread::Builder::new().name("dirty-debug-shutdown".to_owned()).spawn(move || {
+                if let Err(e) = sink.flush() {
+                    eprintln!("dirty-debug: failed to flush \"{uri}\": {e}");
+                }
+
+                drop(sink);
+
+                let _ = sender.send(uri);
+            });
+
+        match spawned {
+            Ok(_) => pending.push(uri),
+            Err(e) => eprintln!("dirty-debug: failed to shut down \"{uri}\": {e}"),
+        }
+    }
+
+    while !pending.is_empty() {
+        let Ok(uri) = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        else {
+            break;
+        };
+
+        pending.retain(|&p| p != uri);
+    }
+
+    pending
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::record::Record;
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    struct SlowSink {
+        flush_time: Duration,
+        flushed: Arc<AtomicBool>,
+    }
+
+    impl Sink for SlowSink {
+        fn write(&mut self, _record: &Record<'_>) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            std::thread::sleep(self.flush_time);
+            self.flushed.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_close_until() {
+        let fast_flushed = Arc::new(AtomicBool::new(false));
+        let slow_flushed = Arc::new(AtomicBool::new(false));
+        let sinks: Vec<(&'static str, Box<dyn Sink>)> = vec![
+            (
+                "fast://",
+                Box::new(SlowSink {
+                    flush_time: Duration::ZERO,
+                    flushed: Arc::clone(&fast_flushed),
+                }),
+            ),
+            (
+                "slow://",
+                Box::new(SlowSink {
+                    flush_time: Duration::from_secs(60),
+                    flushed: Arc::clone(&slow_flushed),
+                }),
+            ),
+        ];
+
+        let start = Instant::now();
+        let pending = close_until(sinks, start + Duration::from_millis(200));
+
+        assert_eq!(pending, ["slow://"]);
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(fast_flushed.load(Ordering::Relaxed));
+        assert!(!slow_flushed.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_run_until() {
+        let start = Instant::now();
+
+        assert!(run_until(|| (), start + Duration::from_secs(60)));
+        assert!(!run_until(
+            || std::thread::sleep(Duration::from_secs(60)),
+            start + Duration::from_millis(200),
+        ));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
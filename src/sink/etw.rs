@@ -0,0 +1,184 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::ffi::c_void;
+use std::io;
+
+/// The level of the events we write, which corresponds to `TRACE_LEVEL_INFORMATION`.
+const LEVEL_INFORMATION: u8 = 4;
+
+/// The namespace used to derive the guid of a provider from its name, like `EventSource` does.
+const PROVIDER_NAMESPACE: [u8; 16] = [
+    0x48, 0x2c, 0x2d, 0xb2, 0xc3, 0x90, 0x47, 0xc8, 0x87, 0xf8, 0x1a, 0x15, 0xbf, 0xc1, 0x30, 0xfb,
+];
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq)]
+struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+#[link(name = "advapi32")]
+extern "system" {
+    fn EventRegister(
+        provider_id: *const Guid,
+        enable_callback: *const c_void,
+        callback_context: *mut c_void,
+        reg_handle: *mut u64,
+    ) -> u32;
+    fn EventUnregister(reg_handle: u64) -> u32;
+    fn EventWriteString(reg_handle: u64, level: u8, keyword: u64, string: *const u16) -> u32;
+}
+
+// The names are the ones of the specification (RFC 3174).
+#[allow(clippy::many_single_char_names)]
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+    let mut message = data.to_vec();
+
+    message.push(0x80);
+    message.resize(message.len().next_multiple_of(64) - 8, 0);
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0_u32; 80];
+
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+
+        for (i, &w) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp =
+                a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(w);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0; 20];
+
+    for (chunk, h) in digest.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+
+    digest
+}
+
+/// The guid of the provider with the given name, which is how tools like `wpr` and `xperf` find
+/// the provider when given `*Name`.
+fn provider_guid(name: &str) -> Guid {
+    let mut data = PROVIDER_NAMESPACE.to_vec();
+
+    data.extend(name.to_uppercase().encode_utf16().flat_map(u16::to_be_bytes));
+
+    let hash = sha1(&data);
+
+    Guid {
+        data1: u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]),
+        data2: u16::from_le_bytes([hash[4], hash[5]]),
+        data3: u16::from_le_bytes([hash[6], (hash[7] & 0x0f) | 0x50]),
+        data4: [hash[8], hash[9], hash[10], hash[11], hash[12], hash[13], hash[14], hash[15]],
+    }
+}
+
+/// Writes every record as an ETW string event, so it can be correlated with kernel events in a
+/// trace (e.g. in WPA).
+pub(super) struct EtwSink {
+    handle: u64,
+    buffer: Vec<u16>,
+}
+
+/// Registers an ETW provider with the given name, e.g. `MyProvider`.
+pub(super) fn open(name: &str) -> io::Result<EtwSink> {
+    let guid = provider_guid(name);
+    let mut handle: u64 = 0;
+
+    // SAFETY: `guid` and `handle` are valid, and we do not need an enable callback.
+    let error =
+        unsafe { EventRegister(&guid, std::ptr::null(), std::ptr::null_mut(), &mut handle) };
+
+    match error {
+        0 => Ok(EtwSink { handle, buffer: Vec::with_capacity(512) }),
+        error => Err(io::Error::from_raw_os_error(i32::try_from(error).unwrap_or(i32::MAX))),
+    }
+}
+
+impl Sink for EtwSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let buffer = &mut self.buffer;
+
+        buffer.clear();
+        buffer.extend(record.to_string().encode_utf16());
+        buffer.push(0);
+
+        // SAFETY: The provider is registered and the buffer is a valid, nul-terminated, wide
+        // string.  This does nothing if no one is tracing the provider.
+        let error = unsafe { EventWriteString(self.handle, LEVEL_INFORMATION, 0, buffer.as_ptr()) };
+
+        match error {
+            0 => Ok(()),
+            error => Err(io::Error::from_raw_os_error(i32::try_from(error).unwrap_or(i32::MAX))),
+        }
+    }
+}
+
+impl Drop for EtwSink {
+    fn drop(&mut self) {
+        // SAFETY: The provider is registered, and it is not used after this.
+        unsafe { EventUnregister(self.handle) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sha1() {
+        assert_eq!(sha1(b"")[..4], [0xda, 0x39, 0xa3, 0xee]);
+        assert_eq!(sha1(b"")[16..], [0xaf, 0xd8, 0x07, 0x09]);
+        assert_eq!(sha1(b"abc")[..4], [0xa9, 0x99, 0x3e, 0x36]);
+        assert_eq!(sha1(b"abc")[16..], [0x9c, 0xd0, 0xd8, 0x9d]);
+    }
+
+    #[test]
+    fn test_provider_guid() {
+        // From the `TraceLoggingProvider.h` documentation.
+        assert_eq!(
+            provider_guid("SimpleTraceLoggingProvider"),
+            Guid {
+                data1: 0x0205_c616,
+                data2: 0xcf97,
+                data3: 0x5c11,
+                data4: [0x97, 0x56, 0x56, 0xa2, 0xce, 0xe0, 0x2c, 0xa7],
+            }
+        );
+    }
+}
@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::ffi::c_void;
+use std::io;
+
+const EVENTLOG_INFORMATION_TYPE: u16 = 0x0004;
+
+#[link(name = "advapi32")]
+extern "system" {
+    fn RegisterEventSourceW(unc_server_name: *const u16, source_name: *const u16) -> *mut c_void;
+    fn DeregisterEventSource(event_log: *mut c_void) -> i32;
+    fn ReportEventW(
+        event_log: *mut c_void,
+        event_type: u16,
+        category: u16,
+        event_id: u32,
+        user_sid: *mut c_void,
+        num_strings: u16,
+        data_size: u32,
+        strings: *const *const u16,
+        raw_data: *mut c_void,
+    ) -> i32;
+}
+
+/// Writes every record as an information event to the Windows Event Log, where it can be seen
+/// with Event Viewer (under "Windows Logs > Application", unless the source is registered
+/// elsewhere).  Since we have no message file, Event Viewer says it cannot find the description
+/// of the event, but the message is shown after that.
+pub(super) struct EventLogSink {
+    handle: *mut c_void,
+    buffer: Vec<u16>,
+}
+
+// SAFETY: Event log handles can be used from any thread.
+unsafe impl Send for EventLogSink {}
+// SAFETY: `EventLogSink` has no interior mutability.
+unsafe impl Sync for EventLogSink {}
+
+/// Opens the event log with the given source name, e.g. `MyService`.
+pub(super) fn open(source: &str) -> io::Result<EventLogSink> {
+    let source: Vec<u16> = source.encode_utf16().chain([0]).collect();
+
+    // SAFETY: `source` is a valid, nul-terminated, wide string, and a null server name means the
+    // local computer.
+    let handle = unsafe { RegisterEventSourceW(std::ptr::null(), source.as_ptr()) };
+
+    match handle.is_null() {
+        true => Err(io::Error::last_os_error()),
+        false => Ok(EventLogSink { handle, buffer: Vec::with_capacity(512) }),
+    }
+}
+
+impl Sink for EventLogSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let buffer = &mut self.buffer;
+
+        buffer.clear();
+        buffer.extend(record.to_string().encode_utf16());
+        buffer.push(0);
+
+        let strings = [buffer.as_ptr()];
+
+        // SAFETY: The handle is open, and we pass one valid, nul-terminated, wide string.
+        let reported = unsafe {
+            ReportEventW(
+                self.handle,
+                EVENTLOG_INFORMATION_TYPE,
+                0,
+                0,
+                std::ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                std::ptr::null_mut(),
+            )
+        };
+
+        match reported {
+            0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for EventLogSink {
+    fn drop(&mut self) {
+        // SAFETY: The handle is open, and it is not used after this.
+        unsafe { DeregisterEventSource(self.handle) };
+    }
+}
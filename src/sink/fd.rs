@@ -0,0 +1,43 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::LineSink;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::mem::ManuallyDrop;
+use std::os::fd::{FromRawFd, RawFd};
+use std::str::FromStr;
+
+/// A writer for a file descriptor we don't own, so we never close it.
+pub(super) struct BorrowedFdWriter {
+    file: ManuallyDrop<File>,
+}
+
+impl Write for BorrowedFdWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Opens a sink for an already open file descriptor, e.g. one inherited from the parent process.
+pub(super) fn open(target: &str) -> io::Result<LineSink<BorrowedFdWriter>> {
+    let fd = RawFd::from_str(target)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid file descriptor"))?;
+
+    // SAFETY: `F_GETFD` only checks if the file descriptor is valid.
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: The file descriptor is open and, since we never drop the file, we never close it.
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+
+    Ok(LineSink::new(BorrowedFdWriter { file }))
+}
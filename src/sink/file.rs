@@ -0,0 +1,230 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::{LineSink, Sink, DIRTY_SINKS};
+use crate::time::UtcDateTime;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+fn open_file(filepath: &Path) -> io::Result<File> {
+    File::options().create(true).append(true).open(filepath)
+}
+
+fn is_not_writable(error: &io::Error) -> bool {
+    #[cfg(unix)]
+    if error.raw_os_error() == Some(libc::EROFS) {
+        return true;
+    }
+
+    error.kind() == io::ErrorKind::PermissionDenied
+}
+
+/// The platform's per-user cache directory.
+fn cache_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+
+    if cfg!(windows) {
+        var("LOCALAPPDATA")
+    } else if cfg!(target_vendor = "apple") {
+        var("HOME").map(|home| home.join("Library").join("Caches"))
+    } else {
+        var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))
+    }
+}
+
+/// Where we log to when `filepath` is not writable: the same path, but under `dir/dirty-debug`,
+/// so that different files do not get mixed up.
+fn fallback_filepath(dir: &Path, filepath: &Path) -> PathBuf {
+    let relative: PathBuf =
+        filepath.components().filter(|c| matches!(c, Component::Normal(_))).collect();
+
+    dir.join("dirty-debug").join(relative)
+}
+
+/// Opens the file in a writable directory when `filepath` is not writable, e.g. in containers
+/// with a read-only root filesystem.
+fn open_fallback(filepath: &Path) -> Option<(PathBuf, File)> {
+    [cache_dir(), Some(std::env::temp_dir())].into_iter().flatten().find_map(|dir| {
+        let fallback = fallback_filepath(&dir, filepath);
+
+        std::fs::create_dir_all(fallback.parent()?).ok()?;
+        open_file(&fallback).ok().map(|file| (fallback, file))
+    })
+}
+
+/// A sink that writes to a file, which might not be the one it was opened with (see
+/// [`open_fallback()`]).
+pub(super) struct FileSink {
+    sink: LineSink<File>,
+    filepath: PathBuf,
+}
+
+impl FileSink {
+    fn create(filepath: PathBuf) -> io::Result<FileSink> {
+        Ok(FileSink { sink: LineSink::new(open_file(&filepath)?), filepath })
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        self.sink.write(record)
+    }
+
+    fn filepath(&self) -> Option<&Path> {
+        Some(&self.filepath)
+    }
+}
+
+pub(super) fn open(filepath: &str) -> io::Result<FileSink> {
+    match FileSink::create(PathBuf::from(filepath)) {
+        Ok(sink) => Ok(sink),
+        Err(e) if is_not_writable(&e) => {
+            let Some((fallback, file)) = open_fallback(Path::new(filepath)) else {
+                return Err(e);
+            };
+            let message = format!(
+                "cannot write to \"{filepath}\" ({e}): writing to \"{}\" instead",
+                fallback.display()
+            );
+            let mut sink = FileSink { sink: LineSink::new(file), filepath: fallback };
+
+            eprintln!("dirty-debug: warning: {message}");
+            sink.write(&Record { location: None, message: &message, prefix: "" })?;
+
+            Ok(sink)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Appends a whole entry (e.g. a row or a record) to a file opened in append mode.
+///
+/// The entry is handed to the OS in a single `write()`, which for a regular file normally writes
+/// all of it, so that even if the program crashes the file will not end with a partial entry.  This
+/// is not guaranteed (e.g. the disk can fill up), and then the rest is written with further calls,
+/// so a crash in between does leave a partial entry.
+pub(crate) fn append_entry(file: &mut File, entry: &[u8]) -> io::Result<()> {
+    file.write_all(entry)
+}
+
+/// Moves what was logged so far to the file `uri` to a snapshot file and continues logging to a
+/// fresh file.  The snapshot file has the same path with a timestamp appended, e.g.
+/// `/tmp/log.20261015T143205.123456Z`, and it is returned.  If the log went to the cache directory
+/// because the file was not writable, that is the file that is snapshotted.
+///
+/// This is done atomically: no message is lost or written to the wrong file, even if other
+/// threads are logging to the same file at the same time.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg;
+/// #
+/// ddbg!("/tmp/log", "Warming up");
+///
+/// let snapshot = dirty_debug::snapshot("/tmp/log");
+///
+/// ddbg!("/tmp/log", "This is in a fresh file");
+/// ```
+///
+/// # Panics
+///
+/// If `uri` is not a file or if the file cannot be renamed.
+pub fn snapshot(uri: &str) -> PathBuf {
+    let filepath = uri.strip_prefix("file://").unwrap_or(uri);
+
+    assert!(
+        !filepath.contains("://"),
+        "failed to snapshot \"{uri}\": only files can be snapshotted"
+    );
+
+    // We hold the sink while we rename the file, so no one can write to it in the meantime.
+    let sink = DIRTY_SINKS.get_mut(filepath);
+    let actual_filepath: PathBuf = sink
+        .as_ref()
+        .and_then(|sink| sink.filepath())
+        .map_or_else(|| PathBuf::from(filepath), Path::to_path_buf);
+
+    let now = UtcDateTime::now();
+    let snapshot_filepath = PathBuf::from(format!(
+        "{}.{:04}{:02}{:02}T{:02}{:02}{:02}.{:06}Z",
+        actual_filepath.display(),
+        now.year,
+        now.month,
+        now.day,
+        now.hour,
+        now.minute,
+        now.second,
+        now.nanosecond / 1000,
+    ));
+
+    let result = std::fs::rename(&actual_filepath, &snapshot_filepath).and_then(|()| match sink {
+        Some(mut sink) => {
+            *sink = Box::new(FileSink::create(actual_filepath)?);
+            Ok(())
+        }
+        None => Ok(()),
+    });
+
+    if let Err(e) = result {
+        panic!("failed to snapshot \"{uri}\": {e}");
+    }
+
+    snapshot_filepath
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fallback_filepath() {
+        let dir = Path::new("/home/user/.cache");
+
+        assert_eq!(
+            fallback_filepath(dir, Path::new("/tmp/log")),
+            Path::new("/home/user/.cache/dirty-debug/tmp/log"),
+        );
+        assert_eq!(
+            fallback_filepath(dir, Path::new("log")),
+            Path::new("/home/user/.cache/dirty-debug/log"),
+        );
+        assert_eq!(
+            fallback_filepath(dir, Path::new("/tmp/../var/./log")),
+            Path::new("/home/user/.cache/dirty-debug/tmp/var/log"),
+        );
+    }
+
+    #[test]
+    fn test_snapshot_fallback() {
+        let uri: &'static str = "/nonexistent/test_snapshot_fallback";
+        let fallback: PathBuf =
+            std::env::temp_dir().join(format!("test_snapshot_fallback_{}", std::process::id()));
+        let sink = FileSink::create(fallback.clone()).unwrap();
+
+        // As if the file had fallen back to the cache directory when it was opened.
+        DIRTY_SINKS.insert(uri, Box::new(sink));
+
+        crate::ddbg!(uri, "first");
+
+        let snapshot: PathBuf = snapshot(uri);
+
+        crate::ddbg!(uri, "second");
+
+        let snapshot_log: String = std::fs::read_to_string(&snapshot).unwrap();
+        let log: String = std::fs::read_to_string(&fallback).unwrap();
+
+        std::fs::remove_file(&snapshot).unwrap();
+        std::fs::remove_file(&fallback).unwrap();
+
+        assert!(snapshot.to_string_lossy().starts_with(&format!("{}.", fallback.display())));
+        assert!(snapshot_log.ends_with("] first\n"), "{snapshot_log}");
+        assert!(log.ends_with("] second\n"), "{log}");
+        assert!(!log.contains("first"), "{log}");
+    }
+}
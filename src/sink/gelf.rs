@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::random::random_u64;
+use crate::record::Record;
+use crate::sink::{udp, Sink};
+use crate::{json, process, time};
+use std::fmt::Write;
+use std::io;
+use std::net::UdpSocket;
+
+/// Syslog's `LOG_DEBUG`.
+const LEVEL_DEBUG: u8 = 7;
+
+/// Messages larger than this are split into chunks.  This is the size recommended for networks
+/// other than the local one.
+const MAX_CHUNK_SIZE: usize = 1420;
+const CHUNK_HEADER_SIZE: usize = 12;
+const CHUNK_MAGIC: [u8; 2] = [0x1e, 0x0f];
+const MAX_CHUNKS: usize = 128;
+
+/// Sends every record as a GELF message over UDP.  See
+/// <https://go2docs.graylog.org/current/getting_in_log_data/gelf.html>.
+pub(super) struct GelfSink {
+    socket: UdpSocket,
+    message: String,
+    chunk: Vec<u8>,
+}
+
+/// Opens a GELF sink, e.g. `graylog.example.com:12201`.
+pub(super) fn open(target: &str) -> io::Result<GelfSink> {
+    let socket = udp::connect(target)?;
+
+    Ok(GelfSink {
+        socket,
+        message: String::with_capacity(512),
+        chunk: Vec::with_capacity(MAX_CHUNK_SIZE),
+    })
+}
+
+fn format_message(message: &mut String, record: &Record<'_>) {
+    let timestamp = time::since_epoch();
+
+    message.clear();
+    message.push_str(r#"{"version":"1.1","host":"#);
+    json::push_string(message, process::hostname());
+    message.push_str(r#","short_message":"#);
+    json::push_string(message, record.message);
+    let _ = write!(
+        message,
+        r#","timestamp":{}.{:06},"level":{LEVEL_DEBUG}"#,
+        timestamp.as_secs(),
+        timestamp.subsec_micros(),
+    );
+
+    if let Some(location) = record.location {
+        message.push_str(r#","_file":"#);
+        json::push_string(message, location.file);
+        let _ = write!(message, r#","_line":{}"#, location.line);
+    }
+
+    message.push('}');
+}
+
+impl Sink for GelfSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        format_message(&mut self.message, record);
+
+        let message = self.message.as_bytes();
+
+        if message.len() <= MAX_CHUNK_SIZE {
+            self.socket.send(message)?;
+            return Ok(());
+        }
+
+        let chunks = message.chunks(MAX_CHUNK_SIZE - CHUNK_HEADER_SIZE);
+        let chunk_count = u8::try_from(chunks.len())
+            .ok()
+            .filter(|&count| usize::from(count) <= MAX_CHUNKS)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "message too large for gelf")
+            })?;
+        let message_id = random_u64().to_ne_bytes();
+
+        for (sequence_number, data) in (0..).zip(chunks) {
+            let chunk = &mut self.chunk;
+
+            chunk.clear();
+            chunk.extend_from_slice(&CHUNK_MAGIC);
+            chunk.extend_from_slice(&message_id);
+            chunk.push(sequence_number);
+            chunk.push(chunk_count);
+            chunk.extend_from_slice(data);
+
+            self.socket.send(chunk)?;
+        }
+
+        Ok(())
+    }
+}
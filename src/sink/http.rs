@@ -0,0 +1,349 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::exit;
+use crate::record::Record;
+use crate::sink::{tcp, Sink};
+use crate::uri::Query;
+use std::fmt::Write as _;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How the records are encoded in the body of the requests.
+pub(super) enum Encoding {
+    /// One line per record, as `text/plain`.
+    Lines,
+    /// An OTLP/HTTP JSON request with a log record per record.
+    #[cfg(feature = "otlp")]
+    Otlp,
+    /// A JSON payload for a chat webhook: the template with `{message}` replaced by the lines
+    /// (escaped as a JSON string) and `{process}` by the name of the process.
+    Webhook { template: &'static str },
+}
+
+impl Encoding {
+    /// The webhook encoding for the `template` query parameter, which is either the template
+    /// itself or one of the `slack`, `discord` or `teams` presets (default: `slack`).
+    fn webhook(query: Query<'static>) -> io::Result<Encoding> {
+        let template = match query.get("template") {
+            None | Some("slack" | "teams") => r#"{"text":"{message}"}"#,
+            Some("discord") => r#"{"content":"{message}"}"#,
+            Some(template) if template.contains("{message}") => template,
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the webhook template must contain \"{message}\"",
+                ))
+            }
+        };
+
+        Ok(Encoding::Webhook { template })
+    }
+}
+
+/// Escapes `s` to go inside a JSON string.
+fn json_escape(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+
+    crate::json::push_string(&mut quoted, s);
+
+    quoted[1..quoted.len() - 1].to_owned()
+}
+
+impl Encoding {
+    fn content_type(&self) -> &'static str {
+        match self {
+            Encoding::Lines => "text/plain; charset=utf-8",
+            #[cfg(feature = "otlp")]
+            Encoding::Otlp => "application/json",
+            Encoding::Webhook { .. } => "application/json",
+        }
+    }
+
+    /// Appends the record to what is pending.
+    fn push(&self, pending: &mut String, record: &Record<'_>) {
+        match self {
+            Encoding::Lines | Encoding::Webhook { .. } => {
+                let _ = writeln!(pending, "{record}");
+            }
+            #[cfg(feature = "otlp")]
+            Encoding::Otlp => crate::sink::otlp::push_log_record(pending, record),
+        }
+    }
+
+    /// Turns what is pending into the body of a request.
+    fn body(&self, pending: String) -> String {
+        match self {
+            Encoding::Lines => pending,
+            #[cfg(feature = "otlp")]
+            Encoding::Otlp => crate::sink::otlp::request(&pending),
+            Encoding::Webhook { template } => template
+                .replace("{process}", &json_escape(crate::process::name()))
+                .replace("{message}", &json_escape(pending.trim_end())),
+        }
+    }
+}
+
+/// The records waiting to be posted to an endpoint.
+struct Batch {
+    uri: &'static str,
+    authority: &'static str,
+    hostname: &'static str,
+    port: u16,
+    path: &'static str,
+    query: Query<'static>,
+    encoding: Encoding,
+    /// The records encoded so far.
+    pending: Mutex<String>,
+    /// Held while posting, so that batches reach the endpoint in order.
+    posting: Mutex<()>,
+    /// Set for https.
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<rustls::ClientConfig>>,
+}
+
+impl Batch {
+    fn post(&self, body: &str) -> io::Result<()> {
+        let stream = tcp::connect(self.hostname, self.port, self.query)?;
+
+        stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+
+        #[cfg(feature = "tls")]
+        if let Some(config) = &self.tls {
+            return self.post_over(crate::sink::tls::connect(config, self.hostname, stream)?, body);
+        }
+
+        self.post_over(stream, body)
+    }
+
+    fn post_over(&self, mut stream: impl Read + Write, body: &str) -> io::Result<()> {
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: {}\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {body}",
+            self.path,
+            self.authority,
+            self.encoding.content_type(),
+            body.len(),
+        )?;
+        stream.flush()?;
+
+        let mut status_line = String::new();
+
+        BufReader::new(stream).read_line(&mut status_line)?;
+
+        let status_line = status_line.trim_end();
+
+        match status_line.split(' ').nth(1).is_some_and(|status| status.starts_with('2')) {
+            true => Ok(()),
+            false => Err(io::Error::other(format!("server responded \"{status_line}\""))),
+        }
+    }
+
+    fn flush(&self) {
+        let _posting = self.posting.lock().unwrap_or_else(PoisonError::into_inner);
+        let pending =
+            std::mem::take(&mut *self.pending.lock().unwrap_or_else(PoisonError::into_inner));
+
+        if pending.is_empty() {
+            return;
+        }
+
+        let body = self.encoding.body(pending);
+
+        // This runs in the background, so there is no one to report the error to but `stderr`.
+        if let Err(e) = self.post(&body) {
+            eprintln!("dirty-debug: warning: failed to post to \"{}\": {e}", self.uri);
+        }
+    }
+}
+
+/// Splits the `authority` of an http endpoint into its hostname and port, which defaults to 443
+/// with `tls` and to 80 otherwise.
+fn parse_authority(authority: &str, tls: bool) -> io::Result<(&str, u16)> {
+    // The colons of an IPv6 address like `[::1]` are not a port separator.
+    if authority.rsplit_once(':').is_some_and(|(_, port)| !port.ends_with(']')) {
+        return tcp::parse_authority(authority);
+    }
+
+    let hostname = authority.strip_prefix('[').and_then(|h| h.strip_suffix(']'));
+
+    Ok((hostname.unwrap_or(authority), if tls { 443 } else { 80 }))
+}
+
+/// Collects records and posts them to an http endpoint in batches, by default as a
+/// newline-delimited body.
+pub(super) struct HttpSink {
+    batch: Arc<Batch>,
+    /// Dropping this wakes the thread posting the batches up so that it stops.
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Opens an http sink, e.g. `localhost:8080/log?flush_ms=100`.  The port is optional.  The
+/// `flush_ms` query parameter sets how often the pending records are posted.  With `tls` we use
+/// https, configured by the query parameters of `tls::client_config()`.  The path is
+/// `default_path` if the target has none.
+pub(super) fn open(
+    uri: &'static str,
+    target: &'static str,
+    tls: bool,
+    encoding: Encoding,
+    default_path: &'static str,
+) -> io::Result<HttpSink> {
+    let (target, query) = Query::split(target);
+    let (authority, path) = match target.find('/') {
+        Some(i) => target.split_at(i),
+        None => (target, default_path),
+    };
+    let flush_interval: Duration = query
+        .get_parsed("flush_ms")?
+        .map_or(DEFAULT_FLUSH_INTERVAL, |ms: u64| Duration::from_millis(ms.max(1)));
+
+    let (hostname, port) = parse_authority(authority, tls)?;
+
+    #[cfg(not(feature = "tls"))]
+    if tls {
+        return Err(crate::sink::unsupported("https requires the `tls` feature"));
+    }
+
+    let batch = Arc::new(Batch {
+        uri,
+        authority,
+        hostname,
+        port,
+        path,
+        query,
+        encoding,
+        pending: Mutex::new(String::new()),
+        posting: Mutex::new(()),
+        #[cfg(feature = "tls")]
+        tls: tls.then(|| crate::sink::tls::client_config(query)).transpose()?,
+    });
+
+    // Make sure what is pending is sent when the process exits.
+    exit::flush_sinks_on_exit();
+
+    let (stop, stopped) = mpsc::channel::<()>();
+
+    // The thread stops once the sink is dropped (e.g. by `shutdown_with_timeout()`).
+    let thread = std::thread::Builder::new().name("dirty-debug-http".to_owned()).spawn({
+        let batch = Arc::clone(&batch);
+
+        move || loop {
+            match stopped.recv_timeout(flush_interval) {
+                Err(mpsc::RecvTimeoutError::Timeout) => batch.flush(),
+                Ok(()) | Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    })?;
+
+    Ok(HttpSink { batch, stop: Some(stop), thread: Some(thread) })
+}
+
+/// Opens a webhook sink, e.g. `hooks.slack.com/services/…?template=slack`.  See
+/// [`Encoding::webhook()`] for the template.
+pub(super) fn open_webhook(
+    uri: &'static str,
+    target: &'static str,
+    tls: bool,
+) -> io::Result<HttpSink> {
+    let encoding = Encoding::webhook(Query::split(target).1)?;
+
+    open(uri, target, tls, encoding, "/")
+}
+
+impl Sink for HttpSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let mut pending = self.batch.pending.lock().unwrap_or_else(PoisonError::into_inner);
+
+        self.batch.encoding.push(&mut pending, record);
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.batch.flush();
+        Ok(())
+    }
+
+    fn notifies(&self) -> bool {
+        matches!(self.batch.encoding, Encoding::Webhook { .. })
+    }
+}
+
+impl Drop for HttpSink {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_authority() {
+        assert_eq!(parse_authority("example.com:8080", false).unwrap(), ("example.com", 8080));
+        assert_eq!(parse_authority("example.com:8443", true).unwrap(), ("example.com", 8443));
+        assert_eq!(parse_authority("example.com", false).unwrap(), ("example.com", 80));
+        assert_eq!(parse_authority("example.com", true).unwrap(), ("example.com", 443));
+        assert_eq!(parse_authority("[::1]:8080", false).unwrap(), ("::1", 8080));
+        assert_eq!(parse_authority("[::1]", true).unwrap(), ("::1", 443));
+        assert!(parse_authority("example.com:http", false).is_err());
+    }
+
+    #[test]
+    fn test_open_without_port() {
+        let sink =
+            open("http://example.com/log", "example.com/log", false, Encoding::Lines, "/").unwrap();
+
+        assert_eq!((sink.batch.hostname, sink.batch.port), ("example.com", 80));
+        assert_eq!(sink.batch.path, "/log");
+    }
+
+    #[test]
+    fn test_open_webhook_without_port() {
+        let target = "hooks.slack.com/services/T0/B0?template=slack";
+        let sink = open_webhook("webhook+http://…", target, false).unwrap();
+
+        assert_eq!((sink.batch.hostname, sink.batch.port), ("hooks.slack.com", 80));
+        assert_eq!(sink.batch.path, "/services/T0/B0");
+        assert!(sink.notifies());
+
+        #[cfg(feature = "tls")]
+        assert_eq!(open_webhook("webhook://…", target, true).unwrap().batch.port, 443);
+    }
+
+    #[test]
+    fn test_drop_stops_thread() {
+        let sink =
+            open("http://…", "127.0.0.1:1/log?flush_ms=3600000", false, Encoding::Lines, "/")
+                .unwrap();
+        let batch = Arc::downgrade(&sink.batch);
+        let start = std::time::Instant::now();
+
+        drop(sink);
+
+        // The thread did not wait for the next flush, and it is gone with its handle of the batch.
+        assert!(start.elapsed() < Duration::from_secs(60));
+        assert!(batch.upgrade().is_none());
+    }
+}
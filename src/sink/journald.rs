@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::io;
+use std::os::unix::net::UnixDatagram;
+
+const JOURNALD_SOCKET_PATH: &str = "/run/systemd/journal/socket";
+
+/// Priority of the entries we send, which corresponds to syslog's `LOG_DEBUG`.
+const PRIORITY_DEBUG: u8 = 7;
+
+/// Sends records to journald using its native protocol.  See
+/// <https://systemd.io/JOURNAL_NATIVE_PROTOCOL/>.
+pub(super) struct JournaldSink {
+    socket: UnixDatagram,
+    buffer: Vec<u8>,
+}
+
+/// Opens a journald sink.  If `socket_path` is empty we use the default journald socket.
+pub(super) fn open(socket_path: &str) -> io::Result<JournaldSink> {
+    let socket_path = match socket_path.is_empty() {
+        true => JOURNALD_SOCKET_PATH,
+        false => socket_path,
+    };
+
+    let socket = UnixDatagram::unbound()?;
+
+    socket.connect(socket_path)?;
+
+    Ok(JournaldSink { socket, buffer: Vec::with_capacity(512) })
+}
+
+fn append_field(buffer: &mut Vec<u8>, name: &str, value: &[u8]) {
+    buffer.extend_from_slice(name.as_bytes());
+
+    // Values with newlines need to be length-prefixed.
+    match value.contains(&b'\n') {
+        true => {
+            buffer.push(b'\n');
+            buffer.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        }
+        false => buffer.push(b'='),
+    }
+
+    buffer.extend_from_slice(value);
+    buffer.push(b'\n');
+}
+
+impl Sink for JournaldSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let buffer = &mut self.buffer;
+
+        buffer.clear();
+
+        append_field(buffer, "MESSAGE", record.message.as_bytes());
+        append_field(buffer, "PRIORITY", PRIORITY_DEBUG.to_string().as_bytes());
+
+        if let Some(location) = record.location {
+            append_field(buffer, "CODE_FILE", location.file.as_bytes());
+            append_field(buffer, "CODE_LINE", location.line.to_string().as_bytes());
+        }
+
+        // Journald requires very large entries to be passed via a memfd, but that is way more than
+        // any reasonable debug message.
+        self.socket.send(buffer).map(|_| ())
+    }
+}
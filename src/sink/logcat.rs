@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::ffi::{c_char, c_int, CString};
+use std::io;
+
+/// Priority of the messages we write, which corresponds to `ANDROID_LOG_DEBUG`.
+const ANDROID_LOG_DEBUG: c_int = 3;
+
+const DEFAULT_TAG: &str = "dirty-debug";
+
+#[link(name = "log")]
+extern "C" {
+    fn __android_log_write(prio: c_int, tag: *const c_char, text: *const c_char) -> c_int;
+}
+
+/// Writes records to the Android log, so they show up in `logcat`.
+pub(super) struct LogcatSink {
+    tag: CString,
+}
+
+fn to_c_string(s: String) -> io::Result<CString> {
+    CString::new(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Opens a logcat sink.  If `tag` is empty we use a default tag.
+pub(super) fn open(tag: &str) -> io::Result<LogcatSink> {
+    let tag = match tag.is_empty() {
+        true => DEFAULT_TAG,
+        false => tag,
+    };
+
+    Ok(LogcatSink { tag: to_c_string(tag.to_owned())? })
+}
+
+impl Sink for LogcatSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let text = to_c_string(record.to_string())?;
+
+        // SAFETY: Both the tag and the text are valid, nul-terminated, strings.
+        let result =
+            unsafe { __android_log_write(ANDROID_LOG_DEBUG, self.tag.as_ptr(), text.as_ptr()) };
+
+        match result < 0 {
+            true => Err(io::Error::other("failed to write to the android log")),
+            false => Ok(()),
+        }
+    }
+}
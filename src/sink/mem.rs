@@ -0,0 +1,49 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use dashmap::DashMap;
+use std::fmt::Write;
+use std::io;
+use std::sync::LazyLock;
+
+/// In-memory buffers, indexed by their name.
+static DIRTY_MEM_BUFFERS: LazyLock<DashMap<&str, String>> = LazyLock::new(DashMap::new);
+
+/// Appends records to a named in-memory buffer, which can be read with
+/// [`mem_contents()`](crate::mem_contents).
+pub(super) struct MemSink {
+    name: &'static str,
+}
+
+pub(super) fn open(name: &'static str) -> MemSink {
+    MemSink { name }
+}
+
+impl Sink for MemSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let mut buffer = DIRTY_MEM_BUFFERS.entry(self.name).or_default();
+
+        writeln!(buffer, "{record}").map_err(io::Error::other)
+    }
+}
+
+/// Returns everything that was logged to the in-memory buffer `mem://name`.  If nothing was logged
+/// to that buffer, an empty string is returned.
+///
+/// # Example
+///
+/// ```rust
+/// # use dirty_debug::ddbg;
+/// #
+/// ddbg!("mem://example", "Hello!");
+///
+/// assert!(dirty_debug::mem_contents("example").ends_with("] Hello!\n"));
+/// ```
+#[must_use]
+pub fn mem_contents(name: &str) -> String {
+    DIRTY_MEM_BUFFERS.get(name).map(|buffer| buffer.clone()).unwrap_or_default()
+}
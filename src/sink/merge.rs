@@ -0,0 +1,36 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+/// Writes records to a file of its own inside a directory shared by several processes.  Every line
+/// starts with a timestamp, so that `ddbg-watch` can merge the files of all processes.
+pub(super) struct MergeSink {
+    file: File,
+}
+
+pub(super) fn open(dir: &str) -> io::Result<MergeSink> {
+    std::fs::create_dir_all(dir)?;
+
+    let filename = format!("{}.{}.log", crate::process::name(), std::process::id());
+    let file = File::options().create(true).append(true).open(Path::new(dir).join(filename))?;
+
+    Ok(MergeSink { file })
+}
+
+impl Sink for MergeSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let timestamp = crate::time::since_epoch();
+
+        writeln!(self.file, "{}.{:09} {record}", timestamp.as_secs(), timestamp.subsec_nanos())?;
+
+        self.file.flush()
+    }
+}
@@ -0,0 +1,371 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+#[cfg(all(windows, feature = "etw"))]
+mod etw;
+#[cfg(windows)]
+mod eventlog;
+#[cfg(unix)]
+mod fd;
+mod file;
+mod gelf;
+mod http;
+#[cfg(target_os = "linux")]
+mod journald;
+#[cfg(target_os = "android")]
+mod logcat;
+mod mem;
+mod merge;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod nats;
+#[cfg(not(windows))]
+mod notify;
+mod null;
+#[cfg(target_vendor = "apple")]
+mod oslog;
+#[cfg(feature = "otlp")]
+mod otlp;
+#[cfg(windows)]
+mod pipe;
+mod redis;
+mod ringfile;
+#[cfg(unix)]
+mod serial;
+#[cfg(all(unix, not(target_os = "android")))]
+mod shm;
+mod socks;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stall;
+mod statsd;
+mod tcp;
+#[cfg(feature = "tls")]
+mod tls;
+mod udp;
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+mod vsock;
+mod websocket;
+#[cfg(windows)]
+mod windbg;
+#[cfg(feature = "zmq")]
+mod zmq;
+
+use crate::record::Record;
+use crate::sink::http::Encoding;
+use dashmap::mapref::one::RefMut;
+use dashmap::DashMap;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+pub(crate) use file::append_entry;
+pub use file::snapshot;
+pub use mem::mem_contents;
+
+/// Sinks that were already opened, indexed by their uri.
+static DIRTY_SINKS: LazyLock<DashMap<&str, Box<dyn Sink>>> = LazyLock::new(DashMap::new);
+
+/// When the last record was written to each sink, indexed by their uri (only kept with
+/// `DIRTY_DEBUG_DELTA`).
+static DIRTY_LAST_WRITES: LazyLock<DashMap<&str, Instant>> = LazyLock::new(DashMap::new);
+
+/// A destination for log records.
+pub(crate) trait Sink: Send + Sync {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()>;
+
+    /// Writes out whatever the sink buffered.  Most sinks write every record right away.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// The file the sink writes to, if it writes to a file.
+    fn filepath(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Whether every record gets someone's attention (e.g. a chat webhook), in which case we don't
+    /// write our banner and the line saying how long opening took.
+    fn notifies(&self) -> bool {
+        false
+    }
+}
+
+/// A sink that writes every record as a line of text.
+pub(crate) struct LineSink<W> {
+    writer: W,
+}
+
+impl<W> LineSink<W> {
+    pub(crate) fn new(writer: W) -> LineSink<W> {
+        LineSink { writer }
+    }
+}
+
+impl<W: Write + Send + Sync> Sink for LineSink<W> {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        writeln!(self.writer, "{record}")?;
+
+        // Performance won't be great if we flush all the time, but we don't want to lose log lines
+        // if the program crashes.
+        self.writer.flush()
+    }
+}
+
+fn unsupported(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, msg)
+}
+
+fn open(uri: &'static str) -> io::Result<Box<dyn Sink>> {
+    let sink: Box<dyn Sink> = match uri.split_once("://") {
+        None => Box::new(file::open(uri)?),
+        #[cfg(all(windows, feature = "etw"))]
+        Some(("etw", name)) => Box::new(etw::open(name)?),
+        #[cfg(not(all(windows, feature = "etw")))]
+        Some(("etw", _)) => {
+            return Err(unsupported("etw is only supported on windows with the `etw` feature"))
+        }
+        #[cfg(windows)]
+        Some(("eventlog", source)) => Box::new(eventlog::open(source)?),
+        #[cfg(not(windows))]
+        Some(("eventlog", _)) => return Err(unsupported("eventlog is only supported on windows")),
+        #[cfg(unix)]
+        Some(("fd", fd)) => Box::new(fd::open(fd)?),
+        #[cfg(not(unix))]
+        Some(("fd", _)) => return Err(unsupported("file descriptors are only supported on unix")),
+        Some(("file", filepath)) => Box::new(file::open(filepath)?),
+        Some(("tcp", target)) => tcp::open(uri, target)?,
+        Some(("tcp-auto", target)) => tcp::open_auto(uri, target)?,
+        Some(("gelf", target)) => Box::new(gelf::open(target)?),
+        Some(("http", target)) => Box::new(http::open(uri, target, false, Encoding::Lines, "/")?),
+        Some(("https", target)) => Box::new(http::open(uri, target, true, Encoding::Lines, "/")?),
+        #[cfg(target_os = "linux")]
+        Some(("journald", socket_path)) => Box::new(journald::open(socket_path)?),
+        #[cfg(not(target_os = "linux"))]
+        Some(("journald", _)) => return Err(unsupported("journald is only supported on linux")),
+        #[cfg(target_os = "android")]
+        Some(("logcat", tag)) => Box::new(logcat::open(tag)?),
+        #[cfg(not(target_os = "android"))]
+        Some(("logcat", _)) => return Err(unsupported("logcat is only supported on android")),
+        Some(("mem", name)) => Box::new(mem::open(name)),
+        Some(("merge", dir)) => Box::new(merge::open(dir)?),
+        #[cfg(feature = "mqtt")]
+        Some(("mqtt", target)) => Box::new(mqtt::open(target)?),
+        #[cfg(not(feature = "mqtt"))]
+        Some(("mqtt", _)) => return Err(unsupported("mqtt requires the `mqtt` feature")),
+        Some(("nats", target)) => Box::new(nats::open(target)?),
+        #[cfg(not(windows))]
+        Some(("notify", target)) => Box::new(notify::open(target)?),
+        #[cfg(windows)]
+        Some(("notify", _)) => return Err(unsupported("notify is not supported on windows")),
+        Some(("null", _)) => Box::new(null::NullSink),
+        #[cfg(target_vendor = "apple")]
+        Some(("oslog", target)) => Box::new(oslog::open(target)?),
+        #[cfg(not(target_vendor = "apple"))]
+        Some(("oslog", _)) => {
+            return Err(unsupported("oslog is only supported on apple platforms"))
+        }
+        #[cfg(feature = "otlp")]
+        Some(("otlp", target)) => {
+            Box::new(http::open(uri, target, false, Encoding::Otlp, "/v1/logs")?)
+        }
+        #[cfg(not(feature = "otlp"))]
+        Some(("otlp", _)) => return Err(unsupported("otlp requires the `otlp` feature")),
+        #[cfg(windows)]
+        Some(("pipe", target)) => Box::new(pipe::open(target)?),
+        #[cfg(not(windows))]
+        Some(("pipe", _)) => return Err(unsupported("named pipes are only supported on windows")),
+        Some(("redis", target)) => Box::new(redis::open(target)?),
+        Some(("ringfile", target)) => Box::new(ringfile::open(target)?),
+        #[cfg(unix)]
+        Some(("serial", target)) => Box::new(serial::open(target)?),
+        #[cfg(not(unix))]
+        Some(("serial", _)) => return Err(unsupported("serial is only supported on unix")),
+        #[cfg(all(unix, not(target_os = "android")))]
+        Some(("shm", target)) => Box::new(shm::open(target)?),
+        #[cfg(not(all(unix, not(target_os = "android"))))]
+        Some(("shm", _)) => {
+            return Err(unsupported("shared memory is only supported on unix (other than android)"))
+        }
+        #[cfg(feature = "tls")]
+        Some(("tls", target)) => Box::new(tls::open(target)?),
+        #[cfg(not(feature = "tls"))]
+        Some(("tls", _)) => return Err(unsupported("tls requires the `tls` feature")),
+        #[cfg(feature = "sqlite")]
+        Some(("sqlite", filepath)) => Box::new(sqlite::open(filepath)?),
+        #[cfg(not(feature = "sqlite"))]
+        Some(("sqlite", _)) => return Err(unsupported("sqlite requires the `sqlite` feature")),
+        Some(("statsd", target)) => Box::new(statsd::open(target)?),
+        Some(("udp", target)) => Box::new(udp::open(target)?),
+        #[cfg(all(feature = "vsock", target_os = "linux"))]
+        Some(("vsock", target)) => Box::new(vsock::open(target)?),
+        #[cfg(not(all(feature = "vsock", target_os = "linux")))]
+        Some(("vsock", _)) => {
+            return Err(unsupported("vsock is only supported on linux with the `vsock` feature"))
+        }
+        Some(("webhook", target)) => Box::new(http::open_webhook(uri, target, true)?),
+        Some(("webhook+http", target)) => Box::new(http::open_webhook(uri, target, false)?),
+        Some(("ws", target)) => Box::new(websocket::open(target)?),
+        #[cfg(windows)]
+        Some(("windbg", _)) => Box::new(windbg::open()),
+        #[cfg(not(windows))]
+        Some(("windbg", _)) => return Err(unsupported("windbg is only supported on windows")),
+        #[cfg(feature = "zmq")]
+        Some(("zmq", authority)) => Box::new(zmq::open(authority)?),
+        #[cfg(not(feature = "zmq"))]
+        Some(("zmq", _)) => return Err(unsupported("zmq requires the `zmq` feature")),
+        Some(_) => return Err(unsupported("unsupported uri scheme")),
+    };
+
+    Ok(sink)
+}
+
+/// Flushes every sink, e.g. before the process exits.  Errors are written to `stderr`.
+pub(crate) fn flush_all() {
+    for mut entry in DIRTY_SINKS.iter_mut() {
+        if let Err(e) = entry.value_mut().flush() {
+            eprintln!("dirty-debug: failed to flush \"{}\": {e}", entry.key());
+        }
+    }
+}
+
+/// Removes every sink, returning them with their uris.  Logging to one of them afterwards opens
+/// it again.
+pub(crate) fn take_all() -> Vec<(&'static str, Box<dyn Sink>)> {
+    let uris: Vec<&'static str> = active_uris();
+
+    uris.into_iter().filter_map(|uri| DIRTY_SINKS.remove(uri)).collect()
+}
+
+/// Returns the uris of all the sinks that are currently open.
+pub(crate) fn active_uris() -> Vec<&'static str> {
+    DIRTY_SINKS.iter().map(|entry| *entry.key()).collect()
+}
+
+/// Opens the sink and logs how long that took to the sink itself.  If it took longer than
+/// `slow_threshold` (which can happen, e.g., when resolving a hostname), we also warn about it in
+/// `stderr`.
+fn open_timed(uri: &'static str, slow_threshold: Duration) -> io::Result<Box<dyn Sink>> {
+    let start = Instant::now();
+    let mut sink = open(uri)?;
+    let elapsed = start.elapsed();
+    let message = format!("opening \"{uri}\" took {elapsed:?}");
+
+    if elapsed >= slow_threshold {
+        eprintln!("dirty-debug: warning: {message}");
+    }
+
+    if sink.notifies() {
+        return Ok(sink);
+    }
+
+    let prefix = crate::prefix::prefix();
+
+    if crate::config::banner() {
+        let banner = format!(
+            "session started: name={} pid={} build={}",
+            crate::process::name(),
+            std::process::id(),
+            crate::process::executable_fingerprint(),
+        );
+
+        sink.write(&Record { location: None, message: &banner, prefix: &prefix })?;
+    }
+
+    sink.write(&Record { location: None, message: &message, prefix: &prefix })?;
+
+    Ok(sink)
+}
+
+/// Returns the sink identified by `uri`, opening it if this is the first time it is used.
+fn cached(uri: &'static str) -> io::Result<RefMut<'static, &'static str, Box<dyn Sink>>> {
+    // Both `/tmp/log` and `file:///tmp/log` should share the same sink.
+    let key = uri.strip_prefix("file://").unwrap_or(uri);
+
+    DIRTY_SINKS
+        .entry(key)
+        .or_try_insert_with(|| open_timed(key, crate::config::slow_open_threshold()))
+}
+
+/// Calls `f` with every destination of a `tee:a|b|c` uri, returning the first error (after all the
+/// destinations are done).
+fn for_each_teed(
+    uris: &'static str,
+    mut f: impl FnMut(&'static str) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut result = Ok(());
+
+    for uri in uris.split('|') {
+        if let Err(e) = f(uri) {
+            result = result.and(Err(io::Error::new(e.kind(), format!("\"{uri}\": {e}"))));
+        }
+    }
+
+    result
+}
+
+/// Opens the sink identified by `uri`, unless it is already open.
+pub(crate) fn open_cached(uri: &'static str) -> io::Result<()> {
+    if let Some(uris) = uri.strip_prefix("tee:") {
+        return for_each_teed(uris, open_cached);
+    }
+
+    cached(uri).map(drop)
+}
+
+/// Writes the record to the sink identified by `uri`, opening it if this is the first time it is
+/// used.  A `tee:a|b|c` uri writes the record to each destination, which have their own sinks
+/// (shared with anyone else logging to them).
+pub(crate) fn write(uri: &'static str, record: &Record<'_>) -> io::Result<()> {
+    if let Some(uris) = uri.strip_prefix("tee:") {
+        return for_each_teed(uris, |uri| write(uri, record));
+    }
+
+    // `DashMap` ensures we have exclusive access to this sink, so there is no way for two threads
+    // to write to the same line.
+    let mut sink = cached(uri)?;
+
+    if crate::config::delta() {
+        let now = Instant::now();
+        let previous = DIRTY_LAST_WRITES.insert(*sink.key(), now);
+        let mut prefix = record.prefix.to_owned();
+
+        crate::prefix::push_delta(
+            &mut prefix,
+            previous.map(|previous| now.saturating_duration_since(previous)),
+        );
+
+        return sink.value_mut().write(&Record { prefix: &prefix, ..*record });
+    }
+
+    sink.value_mut().write(record)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_open_timed_slow() {
+        open_timed("mem://test_open_timed_slow", Duration::ZERO).unwrap();
+
+        let log = crate::mem_contents("test_open_timed_slow");
+
+        assert!(log.starts_with("[dirty-debug] opening \"mem://test_open_timed_slow\" took "));
+    }
+
+    #[test]
+    fn test_open_timed_fast() {
+        open_timed("mem://test_open_timed_fast", Duration::from_secs(3600)).unwrap();
+
+        let log = crate::mem_contents("test_open_timed_fast");
+
+        // Only the warning in `stderr` is reserved for slow opens.
+        assert!(log.starts_with("[dirty-debug] opening \"mem://test_open_timed_fast\" took "));
+        assert_eq!(log.lines().count(), 1, "{log}");
+    }
+}
@@ -0,0 +1,136 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::{tcp, Sink};
+use crate::uri::Query;
+use std::io;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+const PACKET_CONNECT: u8 = 0x10;
+const PACKET_CONNACK: u8 = 0x20;
+const PACKET_PUBLISH: u8 = 0x30;
+
+const PROTOCOL_LEVEL_3_1_1: u8 = 4;
+const CONNECT_FLAG_CLEAN_SESSION: u8 = 0x02;
+
+/// Publishes every record to a topic of an MQTT broker, delivered at most once.  See
+/// <https://docs.oasis-open.org/mqtt/mqtt/v3.1.1/mqtt-v3.1.1.html>.
+pub(super) struct MqttSink {
+    stream: TcpStream,
+    topic: &'static str,
+    packet: Vec<u8>,
+}
+
+fn push_remaining_length(packet: &mut Vec<u8>, mut len: usize) -> io::Result<()> {
+    if len > 268_435_455 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "message too large for mqtt"));
+    }
+
+    loop {
+        let byte = u8::try_from(len % 128).unwrap_or_default();
+
+        len /= 128;
+
+        if len == 0 {
+            packet.push(byte);
+            return Ok(());
+        }
+
+        packet.push(byte | 0x80);
+    }
+}
+
+fn push_string(buffer: &mut Vec<u8>, s: &str) -> io::Result<()> {
+    let len = u16::try_from(s.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "string too long for mqtt"))?;
+
+    buffer.extend_from_slice(&len.to_be_bytes());
+    buffer.extend_from_slice(s.as_bytes());
+
+    Ok(())
+}
+
+fn connect(stream: &mut TcpStream) -> io::Result<()> {
+    let mut body: Vec<u8> = Vec::with_capacity(64);
+
+    push_string(&mut body, "MQTT")?;
+    body.push(PROTOCOL_LEVEL_3_1_1);
+    body.push(CONNECT_FLAG_CLEAN_SESSION);
+    // A keep alive of zero disables it, since we may not send anything for a long time.
+    body.extend_from_slice(&0_u16.to_be_bytes());
+    push_string(&mut body, &format!("dirty-debug-{}", std::process::id()))?;
+
+    let mut packet: Vec<u8> = vec![PACKET_CONNECT];
+
+    push_remaining_length(&mut packet, body.len())?;
+    packet.extend_from_slice(&body);
+
+    stream.write_all(&packet)?;
+
+    let mut connack: [u8; 4] = [0; 4];
+
+    stream.read_exact(&mut connack)?;
+
+    match connack {
+        [PACKET_CONNACK, 2, _, 0] => Ok(()),
+        [PACKET_CONNACK, 2, _, code] => {
+            Err(io::Error::other(format!("mqtt broker refused the connection (code {code})")))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "invalid mqtt connack")),
+    }
+}
+
+/// Connects to an MQTT broker, e.g. `localhost:1883/some/topic`.
+pub(super) fn open(target: &'static str) -> io::Result<MqttSink> {
+    let (target, query) = Query::split(target);
+    let (authority, topic) = target
+        .split_once('/')
+        .filter(|(_, topic)| !topic.is_empty())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing mqtt topic"))?;
+    let (hostname, port) = tcp::parse_authority(authority)?;
+    let mut stream = tcp::connect(hostname, port, query)?;
+
+    connect(&mut stream)?;
+
+    Ok(MqttSink { stream, topic, packet: Vec::with_capacity(512) })
+}
+
+impl Sink for MqttSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let payload = record.to_string();
+        let packet = &mut self.packet;
+
+        packet.clear();
+        packet.push(PACKET_PUBLISH);
+        push_remaining_length(packet, 2 + self.topic.len() + payload.len())?;
+        push_string(packet, self.topic)?;
+        packet.extend_from_slice(payload.as_bytes());
+
+        self.stream.write_all(packet)?;
+        self.stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push_remaining_length() {
+        let encode = |len: usize| {
+            let mut packet = Vec::new();
+            push_remaining_length(&mut packet, len).map(|()| packet)
+        };
+
+        assert_eq!(encode(0).unwrap(), [0x00]);
+        assert_eq!(encode(127).unwrap(), [0x7f]);
+        assert_eq!(encode(128).unwrap(), [0x80, 0x01]);
+        assert_eq!(encode(16_383).unwrap(), [0xff, 0x7f]);
+        assert_eq!(encode(2_097_152).unwrap(), [0x80, 0x80, 0x80, 0x01]);
+        assert!(encode(268_435_456).is_err());
+    }
+}
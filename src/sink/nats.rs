@@ -0,0 +1,147 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::{tcp, Sink};
+use crate::uri::Query;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+
+/// Publishes every record to a subject of a NATS server.  See
+/// <https://docs.nats.io/reference/reference-protocols/nats-protocol>.
+pub(super) struct NatsSink {
+    stream: Arc<Mutex<TcpStream>>,
+    subject: &'static str,
+    request: Vec<u8>,
+    /// The thread answering the server, which stops once the connection is shut down.
+    thread: Option<JoinHandle<()>>,
+}
+
+fn connect_request(credentials: Option<(&str, &str)>) -> String {
+    let mut request = String::from(r#"CONNECT {"verbose":false,"pedantic":false,"lang":"rust","#);
+
+    request.push_str(r#""name":"#);
+    crate::json::push_string(&mut request, &format!("dirty-debug {}", crate::process::name()));
+
+    if let Some((user, pass)) = credentials {
+        request.push_str(r#","user":"#);
+        crate::json::push_string(&mut request, user);
+        request.push_str(r#","pass":"#);
+        crate::json::push_string(&mut request, pass);
+    }
+
+    request.push_str("}\r\n");
+
+    request
+}
+
+/// Answers the `PING`s of the server, which would otherwise close the connection when we do not
+/// log anything for a while, and reports the errors it sends us.
+fn serve(mut reader: BufReader<TcpStream>, stream: &Mutex<TcpStream>) {
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+
+        if !matches!(reader.read_line(&mut line), Ok(1..)) {
+            return;
+        }
+
+        if line.starts_with("PING") {
+            let mut stream = stream.lock().unwrap_or_else(PoisonError::into_inner);
+
+            if stream.write_all(b"PONG\r\n").is_err() {
+                return;
+            }
+        } else if let Some(error) = line.strip_prefix("-ERR ") {
+            eprintln!("dirty-debug: nats error: {}", error.trim_end());
+        }
+    }
+}
+
+/// Connects to a NATS server, e.g. `localhost:4222/subject` or `user:pass@localhost:4222/subject`.
+pub(super) fn open(target: &'static str) -> io::Result<NatsSink> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg.to_owned());
+
+    let (target, query) = Query::split(target);
+    let (authority, subject) = target
+        .split_once('/')
+        .filter(|(_, subject)| !subject.is_empty())
+        .ok_or_else(|| invalid("missing nats subject"))?;
+
+    if subject.contains(char::is_whitespace) {
+        return Err(invalid("invalid nats subject"));
+    }
+
+    let (credentials, authority) = match authority.rsplit_once('@') {
+        Some((userinfo, authority)) => (userinfo.split_once(':'), authority),
+        None => (None, authority),
+    };
+    let (hostname, port) = tcp::parse_authority(authority)?;
+    let mut reader = BufReader::new(tcp::connect(hostname, port, query)?);
+    let mut info = String::new();
+
+    reader.read_line(&mut info)?;
+
+    if !info.starts_with("INFO ") {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a nats server"));
+    }
+
+    let mut stream = reader.get_ref().try_clone()?;
+
+    stream.write_all(connect_request(credentials).as_bytes())?;
+
+    let stream = Arc::new(Mutex::new(stream));
+
+    let thread = std::thread::Builder::new().name("dirty-debug-nats".to_owned()).spawn({
+        let stream = Arc::clone(&stream);
+
+        move || serve(reader, &stream)
+    })?;
+
+    Ok(NatsSink { stream, subject, request: Vec::with_capacity(512), thread: Some(thread) })
+}
+
+impl Sink for NatsSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let payload = record.to_string();
+        let request = &mut self.request;
+
+        request.clear();
+        request.extend_from_slice(format!("PUB {} {}\r\n", self.subject, payload.len()).as_bytes());
+        request.extend_from_slice(payload.as_bytes());
+        request.extend_from_slice(b"\r\n");
+
+        self.stream.lock().unwrap_or_else(PoisonError::into_inner).write_all(request)
+    }
+}
+
+impl Drop for NatsSink {
+    fn drop(&mut self) {
+        // The thread answering the server has its own handle of the connection, so we need to
+        // shut it down for the connection to close.
+        let _ = self.stream.lock().unwrap_or_else(PoisonError::into_inner).shutdown(Shutdown::Both);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_connect_request() {
+        let request = connect_request(Some(("alice", "se\"cret")));
+
+        assert!(request.starts_with(r#"CONNECT {"verbose":false,"#));
+        assert!(request.ends_with(",\"user\":\"alice\",\"pass\":\"se\\\"cret\"}\r\n"));
+    }
+}
@@ -0,0 +1,142 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use crate::uri::Query;
+use std::io;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Shows every record as a desktop notification, with `notify-send` (which talks to the
+/// `org.freedesktop.Notifications` D-Bus service) or with `osascript` on macOS.  At most one
+/// notification is shown per interval, and the messages in between are only counted.
+pub(super) struct NotifySink {
+    title: &'static str,
+    interval: Duration,
+    last_shown: Option<Instant>,
+    skipped: u64,
+}
+
+/// Opens a notification sink.  The target is the title of the notifications (default: the name of
+/// the process), and the `interval_ms` query parameter sets the minimum time between
+/// notifications.
+pub(super) fn open(target: &'static str) -> io::Result<NotifySink> {
+    let (title, query) = Query::split(target);
+    let interval: Duration =
+        query.get_parsed("interval_ms")?.map_or(DEFAULT_INTERVAL, Duration::from_millis);
+    let title = match title {
+        "" => crate::process::name(),
+        title => title,
+    };
+
+    Ok(NotifySink { title, interval, last_shown: None, skipped: 0 })
+}
+
+/// Quotes `s` as an applescript string.
+#[cfg_attr(not(target_vendor = "apple"), allow(dead_code))]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn notify(title: &str, body: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_vendor = "apple") {
+        let mut command = Command::new("osascript");
+
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title),
+        ));
+
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+
+        command.arg("--app-name=dirty-debug").arg("--").arg(title).arg(body);
+
+        command
+    };
+
+    let status =
+        command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).status()?;
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(io::Error::other(format!("showing the notification failed ({status})"))),
+    }
+}
+
+impl NotifySink {
+    /// The body of the notification for `record`, or `None` if it is too soon to show another one.
+    fn body(&mut self, record: &Record<'_>) -> Option<String> {
+        if self.last_shown.is_some_and(|last_shown| last_shown.elapsed() < self.interval) {
+            self.skipped += 1;
+            return None;
+        }
+
+        let body = match self.skipped {
+            0 => record.to_string(),
+            skipped => format!("{record}\n({skipped} messages were not shown)"),
+        };
+
+        self.last_shown = Some(Instant::now());
+        self.skipped = 0;
+
+        Some(body)
+    }
+}
+
+impl Sink for NotifySink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        match self.body(record) {
+            Some(body) => notify(self.title, &body),
+            None => Ok(()),
+        }
+    }
+
+    // Otherwise the line saying how long opening took would be the notification shown, and the
+    // first message of the program would not.
+    fn notifies(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::record::Location;
+
+    #[test]
+    fn test_applescript_string() {
+        assert_eq!(applescript_string(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+
+    #[test]
+    fn test_body() {
+        let mut sink = open("test?interval_ms=3600000").unwrap();
+        let record = |message| Record {
+            location: Some(Location { file: "src/main.rs", line: 7 }),
+            message,
+            prefix: "",
+        };
+
+        // No line saying how long opening took takes the place of the first message.
+        assert!(sink.notifies());
+
+        assert_eq!(sink.body(&record("first")).as_deref(), Some("[src/main.rs:7] first"));
+        assert_eq!(sink.body(&record("second")), None);
+        assert_eq!(sink.body(&record("third")), None);
+
+        sink.last_shown = None;
+
+        assert_eq!(
+            sink.body(&record("fourth")).as_deref(),
+            Some("[src/main.rs:7] fourth\n(2 messages were not shown)")
+        );
+    }
+}
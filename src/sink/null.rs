@@ -0,0 +1,17 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::io;
+
+/// Discards every record.
+pub(super) struct NullSink;
+
+impl Sink for NullSink {
+    fn write(&mut self, _record: &Record<'_>) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::io;
+
+/// The type of the messages we log, which corresponds to `OS_LOG_TYPE_DEFAULT`.  We don't use
+/// `OS_LOG_TYPE_DEBUG` because those messages are not shown by default.
+const OS_LOG_TYPE_DEFAULT: u8 = 0x00;
+
+/// The format string passed to `os_log`.  It must be `public`, otherwise the message would be
+/// redacted.
+const FORMAT: &CStr = c"%{public}s";
+
+const DEFAULT_CATEGORY: &str = "default";
+
+const ARGUMENTS_BUFFER_SIZE: u32 = 12;
+
+#[repr(C)]
+struct OsLog {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    static __dso_handle: u8;
+
+    fn os_log_create(subsystem: *const c_char, category: *const c_char) -> *mut OsLog;
+
+    // This is what the `os_log()` C macro expands to.
+    fn _os_log_impl(
+        dso: *const c_void,
+        log: *mut OsLog,
+        log_type: u8,
+        format: *const c_char,
+        buffer: *const u8,
+        size: u32,
+    );
+}
+
+/// Writes records to Apple's unified logging system, so they show up in Console.app and in
+/// `log stream`.
+pub(super) struct OsLogSink {
+    log: *mut OsLog,
+}
+
+// SAFETY: `os_log_t` objects are thread-safe.
+unsafe impl Send for OsLogSink {}
+// SAFETY: `os_log_t` objects are thread-safe.
+unsafe impl Sync for OsLogSink {}
+
+fn to_c_string(s: String) -> io::Result<CString> {
+    CString::new(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Opens an `os_log` sink from `subsystem.category`.  The category is whatever comes after the
+/// last dot, so `com.example.app.network` has subsystem `com.example.app` and category `network`.
+pub(super) fn open(target: &str) -> io::Result<OsLogSink> {
+    let (subsystem, category) = target.rsplit_once('.').unwrap_or((target, DEFAULT_CATEGORY));
+    let subsystem = to_c_string(subsystem.to_owned())?;
+    let category = to_c_string(category.to_owned())?;
+
+    // SAFETY: Both strings are valid and nul-terminated.  They only need to live during the call.
+    let log = unsafe { os_log_create(subsystem.as_ptr(), category.as_ptr()) };
+
+    match log.is_null() {
+        true => Err(io::Error::other("failed to create os_log object")),
+        false => Ok(OsLogSink { log }),
+    }
+}
+
+impl Sink for OsLogSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let message = to_c_string(record.to_string())?;
+
+        // This is the argument buffer that the `os_log()` C macro generates for a single
+        // `%{public}s` argument: a summary byte (the buffer has non-scalar items), the number of
+        // arguments, and then the argument descriptor (public string), its size, and its value.
+        let mut buffer: [u8; ARGUMENTS_BUFFER_SIZE as usize] = [0; ARGUMENTS_BUFFER_SIZE as usize];
+
+        buffer[0..4].copy_from_slice(&[0x02, 0x01, 0x22, 0x08]);
+
+        buffer[4..].copy_from_slice(&(message.as_ptr() as u64).to_ne_bytes());
+
+        // SAFETY: The buffer describes a single string argument which points to a valid
+        // nul-terminated string that lives during the call, and the format string is static.
+        unsafe {
+            _os_log_impl(
+                std::ptr::addr_of!(__dso_handle).cast(),
+                self.log,
+                OS_LOG_TYPE_DEFAULT,
+                FORMAT.as_ptr(),
+                buffer.as_ptr(),
+                ARGUMENTS_BUFFER_SIZE,
+            );
+        }
+
+        Ok(())
+    }
+}
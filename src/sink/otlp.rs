@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! The JSON encoding of OTLP/HTTP log export requests.  See
+//! <https://opentelemetry.io/docs/specs/otlp/#otlphttp> and
+//! <https://github.com/open-telemetry/opentelemetry-proto/blob/main/opentelemetry/proto/logs/v1/logs.proto>.
+
+use crate::json::push_string;
+use crate::record::{Location, Record};
+use std::fmt::Write;
+
+/// The `SEVERITY_NUMBER_DEBUG` severity.
+const SEVERITY_NUMBER_DEBUG: u32 = 5;
+
+fn push_attribute(out: &mut String, key: &str, value: &str, value_type: &str) {
+    out.push_str(r#"{"key":"#);
+    push_string(out, key);
+    let _ = write!(out, r#","value":{{"{value_type}":"#);
+    push_string(out, value);
+    out.push_str("}}");
+}
+
+/// Appends the record as an OTLP log record, separated by a comma from the previous ones.
+pub(super) fn push_log_record(out: &mut String, record: &Record<'_>) {
+    let thread = std::thread::current();
+    let thread_name = thread.name().map_or_else(|| format!("{:?}", thread.id()), str::to_owned);
+
+    if !out.is_empty() {
+        out.push(',');
+    }
+
+    let _ = write!(
+        out,
+        r#"{{"timeUnixNano":"{}","severityNumber":{SEVERITY_NUMBER_DEBUG},"severityText":"DEBUG","body":{{"stringValue":"#,
+        crate::time::since_epoch().as_nanos(),
+    );
+    push_string(out, record.message);
+    out.push_str(r#"},"attributes":["#);
+
+    if let Some(Location { file, line }) = record.location {
+        push_attribute(out, "code.filepath", file, "stringValue");
+        out.push(',');
+        push_attribute(out, "code.lineno", &line.to_string(), "intValue");
+        out.push(',');
+    }
+
+    push_attribute(out, "thread.name", &thread_name, "stringValue");
+    out.push_str("]}");
+}
+
+/// Wraps the log records in an export request.
+pub(super) fn request(log_records: &str) -> String {
+    let mut request = String::with_capacity(log_records.len() + 256);
+
+    request.push_str(r#"{"resourceLogs":[{"resource":{"attributes":["#);
+    push_attribute(&mut request, "service.name", crate::process::name(), "stringValue");
+    request.push(',');
+    push_attribute(&mut request, "process.pid", &std::process::id().to_string(), "intValue");
+    request.push_str(r#"]},"scopeLogs":[{"scope":{"name":"dirty-debug"},"logRecords":["#);
+    request.push_str(log_records);
+    request.push_str("]}]}]}");
+
+    request
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push_log_record() {
+        let mut out = String::new();
+        let location = Location { file: "src/main.rs", line: 42 };
+
+        push_log_record(
+            &mut out,
+            &Record { location: Some(location), message: "a \"b\"", prefix: "" },
+        );
+        push_log_record(&mut out, &Record { location: None, message: "c", prefix: "" });
+
+        let (first, second) = out.split_once(r#"]},{"timeUnixNano""#).unwrap();
+
+        assert!(first.starts_with(r#"{"timeUnixNano":""#));
+        assert!(first.contains(r#""severityNumber":5,"severityText":"DEBUG","body":{"stringValue":"a \"b\""},"attributes":[{"key":"code.filepath","value":{"stringValue":"src/main.rs"}},{"key":"code.lineno","value":{"intValue":"42"}},{"key":"thread.name""#));
+        assert!(second.contains(r#""body":{"stringValue":"c"},"attributes":[{"key":"thread.name""#));
+    }
+}
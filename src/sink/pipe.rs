@@ -0,0 +1,17 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::LineSink;
+use std::fs::File;
+use std::io;
+
+/// Connects to a Windows named pipe.  The target `./pipe/ddbg` corresponds to the pipe
+/// `\\.\pipe\ddbg`.  The pipe must have been created by the reading end.
+pub(super) fn open(target: &str) -> io::Result<LineSink<File>> {
+    let pipe_name = format!(r"\\{}", target.replace('/', r"\"));
+    let pipe = File::options().write(true).open(pipe_name)?;
+
+    Ok(LineSink::new(pipe))
+}
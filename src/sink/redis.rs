@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::{tcp, Sink};
+use crate::uri::Query;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+/// Appends every record to a Redis list with `RPUSH`, or publishes it to a channel with `PUBLISH`.
+/// See <https://redis.io/docs/latest/develop/reference/protocol-spec/>.
+pub(super) struct RedisSink {
+    reader: BufReader<TcpStream>,
+    command: &'static str,
+    key: &'static str,
+    request: Vec<u8>,
+    reply: String,
+}
+
+fn push_bulk_string(request: &mut Vec<u8>, s: &[u8]) {
+    request.extend_from_slice(format!("${}\r\n", s.len()).as_bytes());
+    request.extend_from_slice(s);
+    request.extend_from_slice(b"\r\n");
+}
+
+/// Connects to a Redis server, e.g. `localhost:6379/key`.  With the `publish` query parameter the
+/// key is a channel to publish to, e.g. `localhost:6379/channel?publish`.
+pub(super) fn open(target: &'static str) -> io::Result<RedisSink> {
+    let (target, query) = Query::split(target);
+    let (authority, key) = target
+        .split_once('/')
+        .filter(|(_, key)| !key.is_empty())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing redis key"))?;
+    let (hostname, port) = tcp::parse_authority(authority)?;
+    let command = match query.get("publish") {
+        Some(_) => "PUBLISH",
+        None => "RPUSH",
+    };
+
+    let stream = tcp::connect(hostname, port, query)?;
+
+    Ok(RedisSink {
+        reader: BufReader::new(stream),
+        command,
+        key,
+        request: Vec::with_capacity(512),
+        reply: String::new(),
+    })
+}
+
+impl Sink for RedisSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let request = &mut self.request;
+
+        request.clear();
+        request.extend_from_slice(b"*3\r\n");
+        push_bulk_string(request, self.command.as_bytes());
+        push_bulk_string(request, self.key.as_bytes());
+        push_bulk_string(request, record.to_string().as_bytes());
+
+        self.reader.get_mut().write_all(request)?;
+        self.reader.get_mut().flush()?;
+
+        // Both commands reply with an integer, unless something went wrong (e.g. the key holds
+        // something other than a list).
+        self.reply.clear();
+        self.reader.read_line(&mut self.reply)?;
+
+        match self.reply.strip_prefix('-') {
+            Some(error) => Err(io::Error::other(format!("redis error: {}", error.trim_end()))),
+            None if self.reply.is_empty() => Err(io::ErrorKind::UnexpectedEof.into()),
+            None => Ok(()),
+        }
+    }
+}
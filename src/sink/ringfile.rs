@@ -0,0 +1,148 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! A fixed-size file used as a circular buffer, which `ddbg-ringfile` turns back into a plain log.
+//! The layout of the file is:
+//!
+//! * `magic` (8 bytes) — `DDBGRNG1`.
+//! * `capacity` (u64) — The size of the data area.
+//! * `written` (u64) — How many bytes were ever written.
+//! * padding up to `HEADER_SIZE`.
+//! * The data area, where byte `n` of the stream is at `n % capacity`.
+//!
+//! All integers are little endian.
+
+use crate::config::ByteSize;
+use crate::record::Record;
+use crate::sink::Sink;
+use crate::uri::Query;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+const MAGIC: &[u8; 8] = b"DDBGRNG1";
+const HEADER_SIZE: usize = 32;
+const WRITTEN_OFFSET: u64 = 16;
+const DEFAULT_CAPACITY: u64 = 16 << 20;
+
+pub(super) struct RingFileSink {
+    file: File,
+    capacity: u64,
+    written: u64,
+    line: Vec<u8>,
+}
+
+/// Reads the header of an existing ring file, returning its capacity and how many bytes were
+/// written to it.
+fn read_header(file: &mut File) -> Option<(u64, u64)> {
+    let mut header = [0; 24];
+
+    file.read_exact(&mut header).ok()?;
+
+    let field = |offset: usize| u64::from_le_bytes(header[offset..offset + 8].try_into().unwrap());
+
+    (&header[0..8] == MAGIC).then(|| (field(8), field(16)))
+}
+
+/// Opens a ring file, e.g. `/tmp/log?size=16m`.  The `size` query parameter sets the size of the
+/// circular buffer (default: 16 MiB).  If the file is already a ring file of the same size we keep
+/// writing after what is in it, otherwise it starts over.
+pub(super) fn open(target: &str) -> io::Result<RingFileSink> {
+    let (filepath, query) = Query::split(target);
+    let capacity: u64 = query.get_parsed("size")?.map_or(DEFAULT_CAPACITY, |ByteSize(size)| size);
+
+    if capacity == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid size"));
+    }
+
+    let mut file =
+        File::options().create(true).truncate(false).read(true).write(true).open(filepath)?;
+
+    let written = match read_header(&mut file) {
+        Some((c, written)) if c == capacity => written,
+        _ => {
+            let mut header = [0; HEADER_SIZE];
+
+            header[0..8].copy_from_slice(MAGIC);
+            header[8..16].copy_from_slice(&capacity.to_le_bytes());
+
+            file.set_len(0)?;
+            file.rewind()?;
+            file.write_all(&header)?;
+            file.set_len(HEADER_SIZE as u64 + capacity)?;
+
+            0
+        }
+    };
+
+    Ok(RingFileSink { file, capacity, written, line: Vec::with_capacity(512) })
+}
+
+impl Sink for RingFileSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        self.line.clear();
+        writeln!(self.line, "{record}")?;
+
+        // If the line does not fit only its end is kept.
+        let skip =
+            self.line.len().saturating_sub(usize::try_from(self.capacity).unwrap_or(usize::MAX));
+        let line = &self.line[skip..];
+        let start = self.written % self.capacity;
+        let first_len =
+            usize::try_from(self.capacity - start).map_or(line.len(), |len| len.min(line.len()));
+        let (first, second) = line.split_at(first_len);
+
+        self.file.seek(SeekFrom::Start(HEADER_SIZE as u64 + start))?;
+        self.file.write_all(first)?;
+
+        if !second.is_empty() {
+            self.file.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+            self.file.write_all(second)?;
+        }
+
+        self.written += line.len() as u64;
+        self.file.seek(SeekFrom::Start(WRITTEN_OFFSET))?;
+        self.file.write_all(&self.written.to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ringfile_wraps_around() {
+        let filepath =
+            std::env::temp_dir().join(format!("dirty_debug_test_ringfile_{}", std::process::id()));
+        let target = format!("{}?size=32", filepath.display());
+
+        let mut sink = open(&target).unwrap();
+
+        sink.write(&Record { location: None, message: "0123", prefix: "" }).unwrap();
+        sink.write(&Record { location: None, message: "x", prefix: "" }).unwrap();
+
+        let data = std::fs::read(&filepath).unwrap();
+
+        assert_eq!(data.len(), 64);
+        assert_eq!(&data[0..8], MAGIC);
+        assert_eq!(&data[8..16], &32_u64.to_le_bytes());
+        assert_eq!(&data[16..24], &35_u64.to_le_bytes());
+        assert_eq!(&data[32..], b" x\nrty-debug] 0123\n[dirty-debug]");
+
+        // Reopening with the same size keeps writing where it was.
+        drop(sink);
+
+        let mut sink = open(&target).unwrap();
+
+        sink.write(&Record { location: None, message: "y", prefix: "" }).unwrap();
+
+        let data = std::fs::read(&filepath).unwrap();
+
+        assert_eq!(&data[16..24], &51_u64.to_le_bytes());
+        assert_eq!(&data[32..], b" x\n[dirty-debug] y\n[dirty-debug]");
+
+        std::fs::remove_file(&filepath).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::LineSink;
+use crate::uri::Query;
+use std::fs::File;
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+
+const DEFAULT_BAUD_RATE: u32 = 115_200;
+
+#[derive(Clone, Copy)]
+enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+fn invalid_input(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+fn baud_rate_speed(baud_rate: u32) -> io::Result<libc::speed_t> {
+    let speed = match baud_rate {
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19_200 => libc::B19200,
+        38_400 => libc::B38400,
+        57_600 => libc::B57600,
+        115_200 => libc::B115200,
+        230_400 => libc::B230400,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        460_800 => libc::B460800,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        921_600 => libc::B921600,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        1_000_000 => libc::B1000000,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        1_500_000 => libc::B1500000,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        2_000_000 => libc::B2000000,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        3_000_000 => libc::B3000000,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        4_000_000 => libc::B4000000,
+        _ => return Err(invalid_input("unsupported baud rate")),
+    };
+
+    Ok(speed)
+}
+
+fn check(result: libc::c_int) -> io::Result<()> {
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Puts the serial port in raw mode, with 8 data bits, one stop bit, and the given baud rate and
+/// parity.
+fn configure(file: &File, baud_rate: u32, parity: Parity) -> io::Result<()> {
+    let fd = file.as_raw_fd();
+    let speed = baud_rate_speed(baud_rate)?;
+
+    // SAFETY: `termios` is a plain C struct and it is fully initialized by `tcgetattr()`.
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+
+    // SAFETY: `fd` is an open file descriptor and `termios` is a valid pointer.
+    unsafe {
+        check(libc::tcgetattr(fd, &mut termios))?;
+
+        libc::cfmakeraw(&mut termios);
+
+        check(libc::cfsetispeed(&mut termios, speed))?;
+        check(libc::cfsetospeed(&mut termios, speed))?;
+    }
+
+    termios.c_cflag |= libc::CLOCAL | libc::CREAD | libc::CS8;
+    termios.c_cflag &= !(libc::CSTOPB | libc::PARENB | libc::PARODD);
+
+    match parity {
+        Parity::None => (),
+        Parity::Even => termios.c_cflag |= libc::PARENB,
+        Parity::Odd => termios.c_cflag |= libc::PARENB | libc::PARODD,
+    }
+
+    // SAFETY: `fd` is an open file descriptor and `termios` is a valid pointer.
+    unsafe { check(libc::tcsetattr(fd, libc::TCSANOW, &termios)) }
+}
+
+/// Opens a serial port device.  The baud rate and parity can be set with the `baud` and `parity`
+/// query parameters, e.g. `/dev/ttyUSB0?baud=9600&parity=even`.
+pub(super) fn open(target: &str) -> io::Result<LineSink<File>> {
+    let (path, query) = Query::split(target);
+
+    let baud_rate: u32 = query.get_parsed("baud")?.unwrap_or(DEFAULT_BAUD_RATE);
+    let parity = match query.get("parity") {
+        None | Some("none") => Parity::None,
+        Some("even") => Parity::Even,
+        Some("odd") => Parity::Odd,
+        Some(_) => return Err(invalid_input("invalid parity: must be none, even, or odd")),
+    };
+
+    // We don't want the serial port to become the controlling terminal of the process.
+    let file = File::options().write(true).custom_flags(libc::O_NOCTTY).open(path)?;
+
+    configure(&file, baud_rate, parity)?;
+
+    Ok(LineSink::new(file))
+}
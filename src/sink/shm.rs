@@ -0,0 +1,175 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! A ring buffer in shared memory, which another process can tail with `ddbg-shm-tail`.  The
+//! layout of the shared memory is:
+//!
+//! * `magic` (8 bytes) — `DDBGSHM1`.
+//! * `capacity` (u64) — The size of the data area.
+//! * `written` (u64) — How many bytes were ever written, updated (with release ordering) after
+//!   the bytes are in the data area.
+//! * padding up to `HEADER_SIZE`.
+//! * The data area, where byte `n` of the stream is at `n % capacity`.
+//!
+//! All integers are in native byte order.
+
+use crate::config::ByteSize;
+use crate::record::Record;
+use crate::sink::Sink;
+use crate::uri::Query;
+use std::ffi::CString;
+use std::io;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const MAGIC: &[u8; 8] = b"DDBGSHM1";
+const HEADER_SIZE: usize = 64;
+const CAPACITY_OFFSET: usize = 8;
+const WRITTEN_OFFSET: usize = 16;
+const DEFAULT_CAPACITY: u64 = 1 << 20;
+
+pub(super) struct ShmSink {
+    memory: *mut u8,
+    capacity: usize,
+    line: Vec<u8>,
+}
+
+// SAFETY: We have exclusive access to the mapped memory through `&mut self` (other processes only
+// read it).
+unsafe impl Send for ShmSink {}
+// SAFETY: `ShmSink` has no interior mutability.
+unsafe impl Sync for ShmSink {}
+
+impl ShmSink {
+    #[allow(clippy::cast_ptr_alignment)]
+    fn written(&self) -> &AtomicU64 {
+        // SAFETY: The header is inside the mapping, and `WRITTEN_OFFSET` is aligned since the
+        // mapping is page-aligned.
+        unsafe { &*self.memory.add(WRITTEN_OFFSET).cast::<AtomicU64>() }
+    }
+
+    /// The data area.
+    #[cfg(test)]
+    fn data(&self) -> &[u8] {
+        // SAFETY: The data area is inside the mapping.
+        unsafe { std::slice::from_raw_parts(self.memory.add(HEADER_SIZE), self.capacity) }
+    }
+}
+
+/// Opens a shared memory sink, e.g. `ddbg?size=1m`.  The `size` query parameter sets the size of
+/// the ring buffer (default: 1 MiB).
+pub(super) fn open(target: &str) -> io::Result<ShmSink> {
+    let (name, query) = Query::split(target);
+    let capacity: u64 = query.get_parsed("size")?.map_or(DEFAULT_CAPACITY, |ByteSize(size)| size);
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+    let capacity =
+        usize::try_from(capacity).ok().filter(|&c| c > 0).ok_or_else(|| invalid("invalid size"))?;
+    let name = CString::new(format!("/{name}")).map_err(|_| invalid("invalid shm name"))?;
+    let len = HEADER_SIZE + capacity;
+
+    // SAFETY: `name` is a valid C string.
+    let fd = unsafe { libc::shm_open(name.as_ptr(), libc::O_CREAT | libc::O_RDWR, 0o600) };
+
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let off_len = libc::off_t::try_from(len).map_err(|_| invalid("invalid size"))?;
+
+    // SAFETY: `fd` is a valid file descriptor, and the mapping is of the size we set.  The file
+    // descriptor is not needed after the mapping is created.
+    let memory = unsafe {
+        let memory = match libc::ftruncate(fd, off_len) {
+            0 => libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            ),
+            _ => libc::MAP_FAILED,
+        };
+        let error = io::Error::last_os_error();
+
+        libc::close(fd);
+
+        if memory == libc::MAP_FAILED {
+            return Err(error);
+        }
+
+        memory.cast::<u8>()
+    };
+
+    let sink = ShmSink { memory, capacity, line: Vec::with_capacity(512) };
+
+    // SAFETY: The header is inside the mapping.
+    unsafe {
+        std::ptr::copy_nonoverlapping(MAGIC.as_ptr(), memory, MAGIC.len());
+        let capacity = (capacity as u64).to_ne_bytes();
+
+        std::ptr::copy_nonoverlapping(
+            capacity.as_ptr(),
+            memory.add(CAPACITY_OFFSET),
+            capacity.len(),
+        );
+    }
+
+    sink.written().store(0, Ordering::Release);
+
+    Ok(sink)
+}
+
+impl Sink for ShmSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        self.line.clear();
+        writeln!(self.line, "{record}")?;
+
+        // If the line does not fit only its end is kept.
+        let line = &self.line[self.line.len().saturating_sub(self.capacity)..];
+        let written = self.written().load(Ordering::Relaxed);
+        let start = usize::try_from(written % self.capacity as u64).unwrap_or(0);
+        let (first, second) = line.split_at(line.len().min(self.capacity - start));
+
+        // SAFETY: Both parts are within the data area.
+        unsafe {
+            let data = self.memory.add(HEADER_SIZE);
+
+            std::ptr::copy_nonoverlapping(first.as_ptr(), data.add(start), first.len());
+            std::ptr::copy_nonoverlapping(second.as_ptr(), data, second.len());
+        }
+
+        self.written().store(written + line.len() as u64, Ordering::Release);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_shm_ring_buffer() {
+        let name = format!("dirty-debug-test-{}?size=32", std::process::id());
+        let mut sink = open(&name).unwrap();
+
+        sink.write(&Record { location: None, message: "0123", prefix: "" }).unwrap();
+
+        assert_eq!(sink.written().load(Ordering::Acquire), 19);
+        assert_eq!(&sink.data()[0..19], b"[dirty-debug] 0123\n");
+
+        sink.write(&Record { location: None, message: "x", prefix: "" }).unwrap();
+
+        assert_eq!(sink.written().load(Ordering::Acquire), 35);
+        assert_eq!(sink.data(), b" x\nrty-debug] 0123\n[dirty-debug]");
+
+        let name = CString::new(format!("/dirty-debug-test-{}", std::process::id())).unwrap();
+
+        // SAFETY: `name` is a valid C string.
+        unsafe { libc::shm_unlink(name.as_ptr()) };
+    }
+}
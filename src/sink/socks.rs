@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::tcp;
+use std::io;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+const VERSION: u8 = 5;
+const AUTH_NONE: u8 = 0;
+const COMMAND_CONNECT: u8 = 1;
+const ADDRESS_IPV4: u8 = 1;
+const ADDRESS_DOMAIN_NAME: u8 = 3;
+const ADDRESS_IPV6: u8 = 4;
+const REPLY_SUCCEEDED: u8 = 0;
+
+fn reply_error(reply: u8) -> io::Error {
+    let msg = match reply {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "ttl expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    };
+
+    io::Error::other(format!("socks5 proxy failed to connect: {msg}"))
+}
+
+/// Connects to `hostname:port` through the SOCKS5 proxy at `proxy_authority`.  The proxy resolves
+/// the hostname, so it can be a name only the proxy knows about.  See
+/// <https://www.rfc-editor.org/rfc/rfc1928>.
+pub(super) fn connect(proxy_authority: &str, hostname: &str, port: u16) -> io::Result<TcpStream> {
+    let (proxy_hostname, proxy_port) = tcp::parse_authority(proxy_authority)?;
+    let mut stream = TcpStream::connect((proxy_hostname, proxy_port))?;
+
+    stream.write_all(&[VERSION, 1, AUTH_NONE])?;
+
+    let mut method_reply: [u8; 2] = [0; 2];
+
+    stream.read_exact(&mut method_reply)?;
+
+    if method_reply != [VERSION, AUTH_NONE] {
+        return Err(io::Error::other("socks5 proxy requires authentication"));
+    }
+
+    let hostname_len = u8::try_from(hostname.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "hostname too long for socks5"))?;
+    let mut request: Vec<u8> = vec![VERSION, COMMAND_CONNECT, 0, ADDRESS_DOMAIN_NAME, hostname_len];
+
+    request.extend_from_slice(hostname.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply: [u8; 4] = [0; 4];
+
+    stream.read_exact(&mut reply)?;
+
+    match reply {
+        [VERSION, REPLY_SUCCEEDED, _, _] => (),
+        [VERSION, error, _, _] => return Err(reply_error(error)),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid socks5 reply")),
+    }
+
+    // Skip the address the proxy bound to, which we have no use for.
+    let address_len = match reply[3] {
+        ADDRESS_IPV4 => 4,
+        ADDRESS_IPV6 => 16,
+        ADDRESS_DOMAIN_NAME => {
+            let mut len: [u8; 1] = [0];
+
+            stream.read_exact(&mut len)?;
+            usize::from(len[0])
+        }
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid socks5 reply")),
+    };
+
+    stream.read_exact(&mut vec![0; address_len + 2])?;
+
+    Ok(stream)
+}
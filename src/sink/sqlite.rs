@@ -0,0 +1,68 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use crate::time;
+use rusqlite::Connection;
+use std::io;
+use std::sync::{Mutex, PoisonError};
+
+/// Inserts every record as a row of the `messages` table of a sqlite database.
+pub(super) struct SqliteSink {
+    /// A `Connection` is not `Sync`, but we only ever use it through `&mut self`.
+    connection: Mutex<Connection>,
+}
+
+/// Opens a sqlite sink, e.g. `/tmp/debug.db`.  The database is created if needed.
+pub(super) fn open(filepath: &str) -> io::Result<SqliteSink> {
+    let connection = Connection::open(filepath).map_err(io::Error::other)?;
+
+    // With a write-ahead log and no syncing on every commit we don't lose rows if the program
+    // crashes, and inserting is still fast.
+    connection
+        .execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             CREATE TABLE IF NOT EXISTS messages (
+                 id INTEGER PRIMARY KEY,
+                 timestamp REAL NOT NULL,
+                 file TEXT,
+                 line INTEGER,
+                 thread TEXT NOT NULL,
+                 message TEXT NOT NULL
+             );",
+        )
+        .map_err(io::Error::other)?;
+
+    Ok(SqliteSink { connection: Mutex::new(connection) })
+}
+
+impl Sink for SqliteSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let thread = std::thread::current();
+        let thread_name = thread.name().map_or_else(|| format!("{:?}", thread.id()), str::to_owned);
+
+        let connection = self.connection.get_mut().unwrap_or_else(PoisonError::into_inner);
+
+        connection
+            .prepare_cached(
+                "INSERT INTO messages (timestamp, file, line, thread, message)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )
+            .and_then(|mut statement| {
+                statement.execute((
+                    time::since_epoch().as_secs_f64(),
+                    record.location.map(|location| location.file),
+                    record.location.map(|location| location.line),
+                    thread_name,
+                    record.message,
+                ))
+            })
+            .map_err(io::Error::other)?;
+
+        Ok(())
+    }
+}
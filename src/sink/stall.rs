@@ -0,0 +1,305 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::io;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// While stalled, how often we try to write to the sink again.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// While stalled, we divert at most one message to `stderr` per this interval.
+const TRICKLE_INTERVAL: Duration = Duration::from_millis(250);
+
+struct Stall {
+    last_retry: Instant,
+    last_diverted: Option<Instant>,
+    skipped: u64,
+    not_written: u64,
+}
+
+/// Writes every record as a line of text to a writer whose writes time out when it stalls (e.g. a
+/// network stream whose peer stopped reading).  When that happens we warn about it in `stderr`,
+/// and a trickle of the most recent messages gets diverted to `stderr` until the writer recovers.
+///
+/// A write that times out may have written part of a line.  We keep the rest of it and write it
+/// before anything else, so the writer only ever gets whole lines.
+pub(super) struct StallDiverter<W> {
+    writer: W,
+    uri: &'static str,
+    timeout: Duration,
+    retry_interval: Duration,
+    /// Where diverted messages and warnings go, i.e. `stderr`.
+    diverted: Box<dyn Write + Send + Sync>,
+    /// The end of a line whose write timed out.
+    pending: Vec<u8>,
+    line: Vec<u8>,
+    stall: Option<Stall>,
+}
+
+impl<W: Write> StallDiverter<W> {
+    pub(super) fn new(writer: W, uri: &'static str, timeout: Duration) -> StallDiverter<W> {
+        StallDiverter {
+            writer,
+            uri,
+            timeout,
+            retry_interval: RETRY_INTERVAL,
+            diverted: Box::new(io::stderr()),
+            pending: Vec::new(),
+            line: Vec::with_capacity(256),
+            stall: None,
+        }
+    }
+
+    fn divert(&mut self, record: &Record<'_>) {
+        let now = Instant::now();
+        let Some(stall) = &mut self.stall else {
+            return;
+        };
+
+        stall.not_written += 1;
+
+        match stall.last_diverted {
+            Some(last_diverted) if now.duration_since(last_diverted) < TRICKLE_INTERVAL => {
+                stall.skipped += 1;
+            }
+            _ => {
+                // Best effort: there is nothing else we can do if we can't write to `stderr`.
+                let _ = match stall.skipped {
+                    0 => writeln!(self.diverted, "{record}"),
+                    skipped => writeln!(self.diverted, "{record} ({skipped} messages skipped)"),
+                };
+
+                stall.last_diverted = Some(now);
+                stall.skipped = 0;
+            }
+        }
+    }
+
+    /// Writes the pending end of a line, and then the given line.  If a write fails, whatever was
+    /// not written of a line that was started is kept pending, and we tell whether that is the
+    /// given line.
+    fn write_line(&mut self, line: &[u8]) -> Result<(), (io::Error, bool)> {
+        let mut pending = std::mem::take(&mut self.pending);
+        let result = write_or_keep(&mut self.writer, &mut pending);
+
+        self.pending = pending;
+        result.map_err(|e| (e, false))?;
+
+        let mut written = 0;
+        let result = write_all_counted(&mut self.writer, line, &mut written);
+
+        if result.is_err() && written > 0 {
+            self.pending.extend_from_slice(&line[written..]);
+        }
+
+        result.map_err(|e| (e, written > 0))
+    }
+}
+
+/// Writes all of `bytes`, removing what was written from it.
+fn write_or_keep(writer: &mut impl Write, bytes: &mut Vec<u8>) -> io::Result<()> {
+    let mut written = 0;
+    let result = write_all_counted(writer, bytes, &mut written);
+
+    bytes.drain(..written);
+
+    result
+}
+
+/// Like [`Write::write_all()`], but it tells how much was written when it fails.
+fn write_all_counted(writer: &mut impl Write, bytes: &[u8], written: &mut usize) -> io::Result<()> {
+    while *written < bytes.len() {
+        match writer.write(&bytes[*written..]) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => *written += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    writer.flush()
+}
+
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+impl<W: Write + Send + Sync> Sink for StallDiverter<W> {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        if let Some(stall) = &self.stall {
+            if stall.last_retry.elapsed() < self.retry_interval {
+                self.divert(record);
+                return Ok(());
+            }
+        }
+
+        let mut line = std::mem::take(&mut self.line);
+
+        line.clear();
+        writeln!(line, "{record}")?;
+
+        let result = self.write_line(&line);
+
+        self.line = line;
+
+        match result {
+            Ok(()) => {
+                if let Some(stall) = self.stall.take() {
+                    let _ = writeln!(
+                        self.diverted,
+                        "dirty-debug: \"{}\" recovered: {} messages were not written to it",
+                        self.uri, stall.not_written,
+                    );
+                }
+
+                Ok(())
+            }
+            Err((e, _)) if !is_timeout(&e) => Err(e),
+            Err((_, started)) => {
+                if self.stall.is_none() {
+                    let _ = writeln!(
+                        self.diverted,
+                        "dirty-debug: \"{}\" stalled for more than {:?}: \
+                         diverting messages to stderr",
+                        self.uri, self.timeout,
+                    );
+
+                    self.stall = Some(Stall {
+                        last_retry: Instant::now(),
+                        last_diverted: None,
+                        skipped: 0,
+                        not_written: 0,
+                    });
+                }
+
+                // A line that was partially written will be finished, so it is not diverted.
+                if !started {
+                    self.divert(record);
+                }
+
+                if let Some(stall) = &mut self.stall {
+                    stall.last_retry = Instant::now();
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut pending = std::mem::take(&mut self.pending);
+        let result = write_or_keep(&mut self.writer, &mut pending);
+
+        self.pending = pending;
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    /// A writer that accepts a limited number of bytes, timing out after that.
+    struct ThrottledWriter {
+        written: Arc<Mutex<Vec<u8>>>,
+        budget: Arc<Mutex<usize>>,
+    }
+
+    impl Write for ThrottledWriter {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            let mut budget = self.budget.lock().unwrap_or_else(PoisonError::into_inner);
+
+            if *budget == 0 {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+
+            let n = bytes.len().min(*budget);
+
+            *budget -= n;
+            self.written.lock().unwrap_or_else(PoisonError::into_inner).extend(&bytes[..n]);
+
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap_or_else(PoisonError::into_inner).extend(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn contents(buffer: &Arc<Mutex<Vec<u8>>>) -> String {
+        String::from_utf8(buffer.lock().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn test_stall_diverter() {
+        let written: Arc<Mutex<Vec<u8>>> = Arc::default();
+        let diverted: Arc<Mutex<Vec<u8>>> = Arc::default();
+        let budget: Arc<Mutex<usize>> = Arc::new(Mutex::new(35));
+        let writer = ThrottledWriter { written: Arc::clone(&written), budget: Arc::clone(&budget) };
+        let mut sink = StallDiverter::new(writer, "tcp://test", Duration::from_millis(10));
+        let record = |message| Record { location: None, message, prefix: "" };
+
+        sink.retry_interval = Duration::ZERO;
+        sink.diverted = Box::new(SharedBuffer(Arc::clone(&diverted)));
+
+        // The second line times out halfway through, and the third does not get written at all.
+        sink.write(&record("first")).unwrap();
+        sink.write(&record("second line")).unwrap();
+        sink.write(&record("third")).unwrap();
+
+        assert_eq!(contents(&written), "[dirty-debug] first\n[dirty-debug] s");
+
+        *budget.lock().unwrap() = usize::MAX;
+
+        sink.write(&record("fourth")).unwrap();
+
+        assert_eq!(
+            contents(&written),
+            "[dirty-debug] first\n[dirty-debug] second line\n[dirty-debug] fourth\n"
+        );
+        assert_eq!(
+            contents(&diverted),
+            indoc::indoc! {r#"
+                dirty-debug: "tcp://test" stalled for more than 10ms: diverting messages to stderr
+                [dirty-debug] third
+                dirty-debug: "tcp://test" recovered: 1 messages were not written to it
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_stall_diverter_flush_finishes_line() {
+        let written: Arc<Mutex<Vec<u8>>> = Arc::default();
+        let budget: Arc<Mutex<usize>> = Arc::new(Mutex::new(18));
+        let writer = ThrottledWriter { written: Arc::clone(&written), budget: Arc::clone(&budget) };
+        let mut sink = StallDiverter::new(writer, "tcp://test", Duration::from_millis(10));
+
+        sink.diverted = Box::new(io::sink());
+        sink.write(&Record { location: None, message: "message", prefix: "" }).unwrap();
+
+        *budget.lock().unwrap() = usize::MAX;
+        sink.flush().unwrap();
+
+        assert_eq!(contents(&written), "[dirty-debug] message\n");
+    }
+}
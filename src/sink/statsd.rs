@@ -0,0 +1,133 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::{udp, Sink};
+use std::fmt::Write;
+use std::io;
+use std::net::UdpSocket;
+use std::str::FromStr;
+
+/// Turns records into statsd metrics:
+///
+/// * A message already in the statsd format (e.g. `requests:1|c`) is sent as is.
+/// * A message like `queue_len = 42` is sent as a gauge (`queue_len:42|g`).
+/// * Any other message counts how many times its call site logged, e.g. `ddbg.src_main_rs.42:1|c`.
+///
+/// See <https://github.com/statsd/statsd/blob/master/docs/metric_types.md>.
+pub(super) struct StatsdSink {
+    socket: UdpSocket,
+    metric: String,
+}
+
+/// Opens a statsd sink, e.g. `localhost:8125`.
+pub(super) fn open(target: &str) -> io::Result<StatsdSink> {
+    let socket = udp::connect(target)?;
+
+    Ok(StatsdSink { socket, metric: String::with_capacity(128) })
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || ":|@".contains(c))
+}
+
+fn is_statsd_metric(message: &str) -> bool {
+    let Some((name, rest)) = message.split_once(':') else {
+        return false;
+    };
+    let value_and_type = rest.split('|').take(2).collect::<Vec<_>>();
+
+    is_valid_name(name)
+        && matches!(value_and_type[..], [value, "c" | "g" | "ms" | "h" | "s"] if !value.is_empty())
+}
+
+/// Formats the metric of `record` into `metric`, which is left empty if the record has none.
+fn format_metric(metric: &mut String, record: &Record<'_>) {
+    let message = record.message.trim();
+
+    metric.clear();
+
+    if is_statsd_metric(message) {
+        metric.push_str(message);
+        return;
+    }
+
+    let gauge = message
+        .split_once('=')
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .filter(|&(name, value)| is_valid_name(name) && f64::from_str(value).is_ok());
+
+    match (gauge, record.location) {
+        (Some((name, value)), _) => {
+            let _ = write!(metric, "{name}:{value}|g");
+        }
+        (None, Some(location)) => {
+            let file: String = location
+                .file
+                .chars()
+                .map(|c| match c.is_ascii_alphanumeric() || c == '-' {
+                    true => c,
+                    false => '_',
+                })
+                .collect();
+
+            let _ = write!(metric, "ddbg.{file}.{}:1|c", location.line);
+        }
+        // Messages of dirty-debug itself (e.g. how long opening took) are not worth counting.
+        (None, None) => (),
+    }
+}
+
+impl Sink for StatsdSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        format_metric(&mut self.metric, record);
+
+        if self.metric.is_empty() {
+            return Ok(());
+        }
+
+        self.socket.send(self.metric.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::record::Location;
+
+    fn metric(message: &str) -> String {
+        let mut metric = String::new();
+        let location = Location { file: "src/main.rs", line: 42 };
+
+        format_metric(&mut metric, &Record { location: Some(location), message, prefix: "" });
+
+        metric
+    }
+
+    #[test]
+    fn test_format_metric() {
+        assert_eq!(metric("requests:1|c"), "requests:1|c");
+        assert_eq!(metric("latency:320|ms|@0.1"), "latency:320|ms|@0.1");
+        assert_eq!(metric("queue_len = 42"), "queue_len:42|g");
+        assert_eq!(metric("temperature=-3.5"), "temperature:-3.5|g");
+        assert_eq!(metric("Control reached here"), "ddbg.src_main_rs.42:1|c");
+        assert_eq!(metric("x = not a number"), "ddbg.src_main_rs.42:1|c");
+        assert_eq!(metric("state: 3"), "ddbg.src_main_rs.42:1|c");
+    }
+
+    #[test]
+    fn test_format_metric_meta() {
+        let mut metric = String::from("stale");
+        let mut format = |message| {
+            format_metric(&mut metric, &Record { location: None, message, prefix: "" });
+            metric.clone()
+        };
+
+        assert_eq!(format("opening \"statsd://localhost:8125\" took 1.2µs"), "");
+        assert_eq!(format("open_files = 3"), "open_files:3|g");
+    }
+}
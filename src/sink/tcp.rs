@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::stall::StallDiverter;
+use crate::sink::{socks, LineSink, Sink};
+use crate::uri::Query;
+use std::io;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Splits an authority like `example.com:1234` or `[::1]:1234` into its hostname and port.
+pub(super) fn parse_authority(authority: &str) -> io::Result<(&str, u16)> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+    let (hostname, port) = authority.rsplit_once(':').ok_or_else(|| invalid("invalid tcp uri"))?;
+
+    // Ensure sure we can handle IPv6 uris like `tcp://[::1]:1234`:
+    let hostname = hostname.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(hostname);
+    let port = u16::from_str(port).map_err(|_| invalid("invalid port number"))?;
+
+    Ok((hostname, port))
+}
+
+/// Connects to `hostname:port`, through the SOCKS5 proxy of the `proxy` query parameter if it is
+/// set (e.g. `proxy=socks5://127.0.0.1:1080`).
+pub(super) fn connect(hostname: &str, port: u16, query: Query<'_>) -> io::Result<TcpStream> {
+    let Some(proxy) = query.get("proxy") else {
+        return TcpStream::connect((hostname, port));
+    };
+
+    let proxy_authority = proxy.strip_prefix("socks5://").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "unsupported proxy: only socks5:// is supported",
+        )
+    })?;
+
+    socks::connect(proxy_authority, hostname, port)
+}
+
+/// Opens a tcp sink.  If the `stall_ms` query parameter is set, messages are diverted to `stderr`
+/// when writing to the endpoint blocks for longer than that.
+pub(super) fn open(uri: &'static str, target: &str) -> io::Result<Box<dyn Sink>> {
+    let (authority, query) = Query::split(target);
+    let (hostname, port) = parse_authority(authority)?;
+
+    open_endpoint(uri, hostname, port, query)
+}
+
+/// Where `ddbg-listen --auto=<name>` writes the port it listens on.
+fn rendezvous_filepath(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ddbg-rendezvous-{name}"))
+}
+
+/// Opens a tcp sink to the port announced by `ddbg-listen --auto=<name>` in its rendezvous file.
+/// The endpoint is on `localhost`, unless the `host` query parameter is set.
+pub(super) fn open_auto(uri: &'static str, target: &str) -> io::Result<Box<dyn Sink>> {
+    let (name, query) = Query::split(target);
+    let filepath = rendezvous_filepath(name);
+    let hostname = query.get("host").unwrap_or("localhost");
+
+    let contents = std::fs::read_to_string(&filepath).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!(
+                "cannot read rendezvous file \"{}\" (is `ddbg-listen --auto={name}` running?): {e}",
+                filepath.display(),
+            ),
+        )
+    })?;
+    let port = u16::from_str(contents.trim())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid rendezvous file"))?;
+
+    open_endpoint(uri, hostname, port, query)
+}
+
+fn open_endpoint(
+    uri: &'static str,
+    hostname: &str,
+    port: u16,
+    query: Query<'_>,
+) -> io::Result<Box<dyn Sink>> {
+    let stall_timeout: Option<u64> = query.get_parsed("stall_ms")?;
+
+    let stream = connect(hostname, port, query)?;
+
+    let sink: Box<dyn Sink> = match stall_timeout {
+        None => Box::new(LineSink::new(stream)),
+        Some(timeout_ms) => {
+            let timeout = Duration::from_millis(timeout_ms.max(1));
+
+            stream.set_write_timeout(Some(timeout))?;
+
+            Box::new(StallDiverter::new(stream, uri, timeout))
+        }
+    };
+
+    Ok(sink)
+}
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::{tcp, Sink};
+use crate::uri::Query;
+use std::io;
+use std::io::Write;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+
+/// Creates a UDP socket connected to `authority`, e.g. `example.com:1234`.
+pub(super) fn connect(authority: &str) -> io::Result<UdpSocket> {
+    let (hostname, port) = tcp::parse_authority(authority)?;
+    let address: SocketAddr = (hostname, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host not found"))?;
+    let bind_address = match address {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let socket = UdpSocket::bind(bind_address)?;
+
+    socket.connect(address)?;
+
+    Ok(socket)
+}
+
+/// Sends every record as a datagram, which can go to a multicast group.
+pub(super) struct UdpSink {
+    socket: UdpSocket,
+    datagram: Vec<u8>,
+}
+
+/// Opens a udp sink, e.g. `239.1.2.3:5000?ttl=1`.  The `ttl` (IPv4 only) and `loop` query
+/// parameters set how many hops multicast datagrams can go through, and whether they are looped
+/// back to this host.
+pub(super) fn open(target: &str) -> io::Result<UdpSink> {
+    let (authority, query) = Query::split(target);
+    let socket = connect(authority)?;
+    let ttl: Option<u32> = query.get_parsed("ttl")?;
+    let multicast_loop: Option<bool> = query.get_parsed("loop")?;
+
+    match socket.peer_addr()? {
+        SocketAddr::V4(_) => {
+            if let Some(ttl) = ttl {
+                socket.set_multicast_ttl_v4(ttl)?;
+            }
+
+            if let Some(multicast_loop) = multicast_loop {
+                socket.set_multicast_loop_v4(multicast_loop)?;
+            }
+        }
+        SocketAddr::V6(_) => {
+            if ttl.is_some() {
+                let msg = "the ttl query parameter is only supported for IPv4";
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+            }
+
+            if let Some(multicast_loop) = multicast_loop {
+                socket.set_multicast_loop_v6(multicast_loop)?;
+            }
+        }
+    }
+
+    Ok(UdpSink { socket, datagram: Vec::with_capacity(512) })
+}
+
+impl Sink for UdpSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        self.datagram.clear();
+        writeln!(self.datagram, "{record}")?;
+        self.socket.send(&self.datagram)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::sink::LineSink;
+use std::fs::File;
+use std::io;
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::str::FromStr;
+
+fn invalid_input(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Splits a target like `2:1234` into its context id and port.
+fn parse_target(target: &str) -> io::Result<(u32, u32)> {
+    let (cid, port) = target.split_once(':').ok_or_else(|| invalid_input("invalid vsock uri"))?;
+    let cid = u32::from_str(cid).map_err(|_| invalid_input("invalid vsock context id"))?;
+    let port = u32::from_str(port).map_err(|_| invalid_input("invalid port number"))?;
+
+    Ok((cid, port))
+}
+
+fn connect(cid: u32, port: u32) -> io::Result<OwnedFd> {
+    // SAFETY: Plain `socket()` call.  We check the result.
+    let fd = unsafe { libc::socket(libc::AF_VSOCK, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) };
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // SAFETY: `fd` is a valid socket that we own.
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // SAFETY: `sockaddr_vm` is a plain C struct, for which all zeros is a valid value.
+    let mut address: libc::sockaddr_vm = unsafe { std::mem::zeroed() };
+
+    address.svm_family = libc::sa_family_t::try_from(libc::AF_VSOCK).unwrap_or_default();
+    address.svm_cid = cid;
+    address.svm_port = port;
+
+    let address_len = libc::socklen_t::try_from(std::mem::size_of::<libc::sockaddr_vm>())
+        .map_err(io::Error::other)?;
+
+    // SAFETY: `address` is a valid `sockaddr_vm` and `address_len` is its size.
+    let result = unsafe {
+        libc::connect(fd, std::ptr::addr_of!(address).cast::<libc::sockaddr>(), address_len)
+    };
+
+    match result {
+        0 => Ok(socket),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Connects to a vsock endpoint, e.g. `2:1234` to connect to port 1234 of the hypervisor host.
+pub(super) fn open(target: &str) -> io::Result<LineSink<File>> {
+    let (cid, port) = parse_target(target)?;
+    let socket = connect(cid, port)?;
+
+    Ok(LineSink::new(File::from(socket)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(parse_target("2:1234").unwrap(), (2, 1234));
+        assert!(parse_target("2").is_err());
+        assert!(parse_target("host:1234").is_err());
+    }
+}
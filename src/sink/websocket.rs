@@ -0,0 +1,147 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::random::random_u64;
+use crate::record::Record;
+use crate::sink::{tcp, Sink};
+use crate::uri::Query;
+use std::io;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+const OPCODE_TEXT: u8 = 0x1;
+const FIN: u8 = 0x80;
+const MASKED: u8 = 0x80;
+
+/// Sends every record as a text frame of a WebSocket connection.  See
+/// <https://www.rfc-editor.org/rfc/rfc6455>.
+pub(super) struct WebSocketSink {
+    stream: TcpStream,
+    frame: Vec<u8>,
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(char::from(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f])),
+                false => encoded.push('='),
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Reads the http response headers of the handshake and checks that the server agreed to switch
+/// protocols.
+fn read_handshake_response(stream: &mut TcpStream) -> io::Result<()> {
+    let mut response: Vec<u8> = Vec::with_capacity(512);
+    let mut byte: [u8; 1] = [0];
+
+    // We read byte by byte so that we don't consume anything after the headers.
+    while !response.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "websocket handshake failed"));
+        }
+
+        response.push(byte[0]);
+    }
+
+    let status_line = response.split(|&b| b == b'\r').next().unwrap_or_default();
+
+    match status_line.split(|&b| b == b' ').nth(1) {
+        Some(b"101") => Ok(()),
+        _ => Err(io::Error::other(format!(
+            "websocket handshake failed: \"{}\"",
+            String::from_utf8_lossy(status_line),
+        ))),
+    }
+}
+
+/// Connects to a WebSocket server, e.g. `localhost:8080/path`.
+pub(super) fn open(target: &str) -> io::Result<WebSocketSink> {
+    let (target, query) = Query::split(target);
+    let (authority, path) = match target.find('/') {
+        Some(i) => target.split_at(i),
+        None => (target, "/"),
+    };
+    let (hostname, port) = tcp::parse_authority(authority)?;
+    let mut stream = tcp::connect(hostname, port, query)?;
+
+    let mut key: [u8; 16] = [0; 16];
+
+    key[0..8].copy_from_slice(&random_u64().to_ne_bytes());
+    key[8..16].copy_from_slice(&random_u64().to_ne_bytes());
+
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\n\
+         Host: {authority}\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Key: {}\r\n\
+         Sec-WebSocket-Version: 13\r\n\
+         \r\n",
+        base64(&key),
+    )?;
+
+    read_handshake_response(&mut stream)?;
+
+    Ok(WebSocketSink { stream, frame: Vec::with_capacity(512) })
+}
+
+impl Sink for WebSocketSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let payload = record.to_string();
+        let frame = &mut self.frame;
+
+        frame.clear();
+        frame.push(FIN | OPCODE_TEXT);
+
+        // Frames sent by a client must always be masked.
+        match (u8::try_from(payload.len()), u16::try_from(payload.len())) {
+            (Ok(len @ 0..=125), _) => frame.push(MASKED | len),
+            (_, Ok(len)) => {
+                frame.push(MASKED | 0x7e);
+                frame.extend_from_slice(&len.to_be_bytes());
+            }
+            (_, Err(_)) => {
+                frame.push(MASKED | 0x7f);
+                frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+            }
+        }
+
+        let mask: [u8; 4] = random_u64().to_ne_bytes()[0..4].try_into().unwrap_or_default();
+
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.bytes().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+
+        self.stream.write_all(frame)?;
+        self.stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"the sample nonce"), "dGhlIHNhbXBsZSBub25jZQ==");
+    }
+}
@@ -0,0 +1,38 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::Sink;
+use std::io;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn OutputDebugStringW(output_string: *const u16);
+}
+
+/// Sends records to `OutputDebugStringW()`, so that they show up in the debugger attached to the
+/// process.
+pub(super) struct WinDbgSink {
+    buffer: Vec<u16>,
+}
+
+pub(super) fn open() -> WinDbgSink {
+    WinDbgSink { buffer: Vec::with_capacity(512) }
+}
+
+impl Sink for WinDbgSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let buffer = &mut self.buffer;
+
+        buffer.clear();
+        buffer.extend(format!("{record}\n").encode_utf16());
+        buffer.push(0);
+
+        // SAFETY: The buffer is a valid, nul-terminated, wide string.
+        unsafe { OutputDebugStringW(buffer.as_ptr()) };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::record::Record;
+use crate::sink::{tcp, Sink};
+use std::io;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+/// How long we wait for a subscriber to read before we drop it, so that a stuck subscriber does
+/// not block the process.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long we wait for each step of the handshake of a subscriber before we give up on it.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The ZMTP 3.0 greeting, with the `NULL` security mechanism.  See
+/// <https://rfc.zeromq.org/spec/23/>.
+fn greeting() -> [u8; 64] {
+    let mut greeting = [0; 64];
+
+    greeting[0] = 0xff;
+    greeting[9] = 0x7f;
+    greeting[10] = 3;
+    greeting[12..16].copy_from_slice(b"NULL");
+
+    greeting
+}
+
+fn push_frame(buffer: &mut Vec<u8>, flags: u8, body: &[u8]) {
+    if let Ok(len) = u8::try_from(body.len()) {
+        buffer.extend_from_slice(&[flags, len]);
+    } else {
+        buffer.push(flags | FLAG_LONG);
+        buffer.extend_from_slice(&(body.len() as u64).to_be_bytes());
+    }
+
+    buffer.extend_from_slice(body);
+}
+
+/// Reads a frame, returning its flags and body.
+fn read_frame(stream: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut flags = [0; 1];
+
+    stream.read_exact(&mut flags)?;
+
+    let len = if flags[0] & FLAG_LONG == 0 {
+        let mut len = [0; 1];
+
+        stream.read_exact(&mut len)?;
+        u64::from(len[0])
+    } else {
+        let mut len = [0; 8];
+
+        stream.read_exact(&mut len)?;
+        u64::from_be_bytes(len)
+    };
+
+    let mut body = Vec::new();
+
+    stream.take(len).read_to_end(&mut body)?;
+
+    match body.len() as u64 == len {
+        true => Ok((flags[0], body)),
+        false => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+/// Exchanges the greeting and the `READY` command with a subscriber.
+fn handshake(stream: &mut TcpStream) -> io::Result<()> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_owned());
+
+    stream.write_all(&greeting())?;
+
+    let mut peer_greeting = [0; 64];
+
+    stream.read_exact(&mut peer_greeting)?;
+
+    if peer_greeting[0] != 0xff || peer_greeting[9] != 0x7f || peer_greeting[10] < 3 {
+        return Err(invalid("not a zmtp 3 peer"));
+    }
+
+    let mut ready = b"\x05READY\x0bSocket-Type".to_vec();
+
+    ready.extend_from_slice(&3_u32.to_be_bytes());
+    ready.extend_from_slice(b"PUB");
+
+    let mut frame = Vec::new();
+
+    push_frame(&mut frame, FLAG_COMMAND, &ready);
+    stream.write_all(&frame)?;
+
+    match read_frame(stream)? {
+        (flags, body) if flags & FLAG_COMMAND != 0 && body.starts_with(b"\x05READY") => Ok(()),
+        _ => Err(invalid("expected a zmtp ready command")),
+    }
+}
+
+struct Subscriber {
+    stream: TcpStream,
+    /// The topics (i.e. message prefixes) the subscriber subscribed to, or `None` once it
+    /// disconnected.
+    topics: Arc<Mutex<Option<Vec<Vec<u8>>>>>,
+}
+
+/// Reads the subscriptions of a subscriber until it disconnects.
+fn read_subscriptions(mut stream: TcpStream, topics: &Mutex<Option<Vec<Vec<u8>>>>) {
+    while let Ok((flags, body)) = read_frame(&mut stream) {
+        // Subscriptions are messages in ZMTP 3.0 and commands in ZMTP 3.1.
+        let change = match flags & FLAG_COMMAND {
+            0 => body.split_first().map(|(&kind, topic)| (kind == 1, topic)),
+            _ => match body.strip_prefix(b"\x09SUBSCRIBE") {
+                Some(topic) => Some((true, topic)),
+                None => body.strip_prefix(b"\x06CANCEL").map(|topic| (false, topic)),
+            },
+        };
+
+        let mut topics = topics.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(topics) = topics.as_mut() else {
+            return;
+        };
+
+        match change {
+            Some((true, topic)) => topics.push(topic.to_vec()),
+            Some((false, topic)) => {
+                if let Some(i) = topics.iter().position(|t| t == topic) {
+                    topics.swap_remove(i);
+                }
+            }
+            None => (),
+        }
+    }
+
+    *topics.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Does the handshake with a new subscriber, and then reads its subscriptions until it disconnects.
+fn subscribe(mut stream: TcpStream, subscribers: &Mutex<Vec<Subscriber>>) {
+    let reader = stream
+        .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+        .and_then(|()| handshake(&mut stream))
+        .and_then(|()| stream.set_read_timeout(None))
+        .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+        .and_then(|()| stream.try_clone());
+
+    let Ok(reader) = reader else {
+        return;
+    };
+
+    let topics = Arc::new(Mutex::new(Some(Vec::new())));
+
+    subscribers
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Subscriber { stream, topics: Arc::clone(&topics) });
+
+    read_subscriptions(reader, &topics);
+}
+
+/// Accepts subscribers until the sink is closed, and then disconnects them and waits for their
+/// threads to stop.
+fn accept(listener: &TcpListener, subscribers: &Arc<Mutex<Vec<Subscriber>>>, closed: &AtomicBool) {
+    let mut connections: Vec<(TcpStream, JoinHandle<()>)> = Vec::new();
+
+    for stream in listener.incoming() {
+        if closed.load(Ordering::Acquire) {
+            break;
+        }
+
+        let Ok((stream, connection)) = stream.and_then(|s| Ok((s.try_clone()?, s))) else {
+            continue;
+        };
+
+        // The handshake happens in the thread of the subscriber, so that a client that never
+        // completes it does not keep others from subscribing.
+        let subscribers = Arc::clone(subscribers);
+
+        if let Ok(thread) = std::thread::Builder::new()
+            .name("dirty-debug-zmq".to_owned())
+            .spawn(move || subscribe(stream, &subscribers))
+        {
+            connections.retain(|(_, thread)| !thread.is_finished());
+            connections.push((connection, thread));
+        }
+    }
+
+    for (connection, thread) in connections {
+        let _ = connection.shutdown(Shutdown::Both);
+        let _ = thread.join();
+    }
+}
+
+/// Publishes every record as a zeromq message to whoever is subscribed, like a zeromq `PUB`
+/// socket.  Subscribers can come and go at any time, and messages are only sent to the
+/// subscribers whose topic is a prefix of the message (subscribe to the empty topic to get every
+/// message).
+pub(super) struct ZmqSink {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    /// Where we listen for subscribers.
+    address: SocketAddr,
+    /// Set when the sink is dropped, to stop listening for subscribers.
+    closed: Arc<AtomicBool>,
+    /// The thread accepting subscribers, which stops once `closed` is set and it is woken up.
+    thread: Option<JoinHandle<()>>,
+    frame: Vec<u8>,
+}
+
+/// Binds a zeromq publisher, e.g. `*:5556` to bind all interfaces.
+pub(super) fn open(authority: &str) -> io::Result<ZmqSink> {
+    let (hostname, port) = tcp::parse_authority(authority)?;
+
+    let listener = match hostname {
+        "*" => TcpListener::bind((Ipv6Addr::UNSPECIFIED, port))
+            .or_else(|_| TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)))?,
+        _ => TcpListener::bind((hostname, port))?,
+    };
+
+    let address = listener.local_addr()?;
+    let subscribers = Arc::new(Mutex::new(Vec::new()));
+    let closed = Arc::new(AtomicBool::new(false));
+
+    let thread = std::thread::Builder::new().name("dirty-debug-zmq".to_owned()).spawn({
+        let subscribers = Arc::clone(&subscribers);
+        let closed = Arc::clone(&closed);
+
+        move || accept(&listener, &subscribers, &closed)
+    })?;
+
+    Ok(ZmqSink {
+        subscribers,
+        address,
+        closed,
+        thread: Some(thread),
+        frame: Vec::with_capacity(512),
+    })
+}
+
+impl Sink for ZmqSink {
+    fn write(&mut self, record: &Record<'_>) -> io::Result<()> {
+        let message = record.to_string();
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(PoisonError::into_inner);
+
+        self.frame.clear();
+        push_frame(&mut self.frame, 0, message.as_bytes());
+
+        // Subscribers that fail to read the message are dropped, since we might have sent only
+        // part of it.
+        subscribers.retain_mut(|subscriber| {
+            let subscribed =
+                match &*subscriber.topics.lock().unwrap_or_else(PoisonError::into_inner) {
+                    Some(topics) => {
+                        topics.iter().any(|topic| message.as_bytes().starts_with(topic))
+                    }
+                    None => return false,
+                };
+
+            !subscribed || subscriber.stream.write_all(&self.frame).is_ok()
+        });
+
+        Ok(())
+    }
+}
+
+impl Drop for ZmqSink {
+    fn drop(&mut self) {
+        for subscriber in self.subscribers.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+            let _ = subscriber.stream.shutdown(Shutdown::Both);
+        }
+
+        // Wake up the thread accepting subscribers, so it sees that the sink is closed and closes
+        // the listener.
+        let mut address = self.address;
+
+        self.closed.store(true, Ordering::Release);
+
+        match address.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => address.set_ip(Ipv4Addr::LOCALHOST.into()),
+            IpAddr::V6(ip) if ip.is_unspecified() => address.set_ip(Ipv6Addr::LOCALHOST.into()),
+            _ => (),
+        }
+
+        // If we can't wake it up it is left to stop by itself when the next subscriber connects.
+        if TcpStream::connect_timeout(&address, WRITE_TIMEOUT).is_ok() {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push_frame() {
+        let mut buffer = Vec::new();
+
+        push_frame(&mut buffer, 0, b"hi");
+        assert_eq!(buffer, b"\x00\x02hi");
+
+        buffer.clear();
+        push_frame(&mut buffer, FLAG_COMMAND, &[b'x'; 256]);
+        assert_eq!(&buffer[..9], b"\x06\x00\x00\x00\x00\x00\x00\x01\x00");
+        assert_eq!(read_frame(&mut buffer.as_slice()).unwrap(), (0x06, vec![b'x'; 256]));
+    }
+
+    #[test]
+    fn test_drop_stops_threads() {
+        let sink = open("127.0.0.1:0").unwrap();
+        let address = sink.address;
+        // A client that never does the handshake.
+        let mut silent = TcpStream::connect(address).unwrap();
+
+        // Wait for its thread to be running.
+        std::thread::sleep(Duration::from_millis(50));
+        drop(sink);
+
+        // Once the threads are gone, so are the listener and the connections.
+        assert!(TcpStream::connect(address).is_err());
+        silent.set_read_timeout(Some(Duration::from_secs(60))).unwrap();
+        // Our side of the handshake, and then the end of the connection.
+        silent.read_to_end(&mut Vec::new()).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+
+const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Logs a number of bytes in a format that is easy to read at a glance, like `1.4 MiB` (see
+/// [`HumanSize`]).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_size;
+/// # let cache: Vec<u8> = Vec::new();
+/// #
+/// ddbg_size!("/tmp/log", "cache", cache.capacity());
+/// ```
+///
+/// This logs lines like `cache: 1.4 MiB`.
+#[macro_export]
+macro_rules! ddbg_size {
+    ($uri:expr, $label:expr, $bytes:expr $(,)?) => {{
+        $crate::ddbg!($uri, "{}: {}", $label, $crate::HumanSize::from($bytes));
+    }};
+}
+
+/// Formats a number of bytes with the binary unit that suits it, e.g. `512 B`, `1.4 MiB` or
+/// `3.0 GiB`.  Use it to format sizes in any message:
+///
+/// ```rust,no_run
+/// # use dirty_debug::{ddbg, HumanSize};
+/// # let buffer: Vec<u8> = Vec::new();
+/// #
+/// ddbg!("/tmp/log", "buffer grew to {}", HumanSize::from(buffer.len()));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HumanSize(pub u64);
+
+macro_rules! impl_human_size_from {
+    ($($type:ty),+) => {
+        $(
+            impl From<$type> for HumanSize {
+                fn from(bytes: $type) -> HumanSize {
+                    HumanSize(u64::try_from(bytes).unwrap_or(u64::MAX))
+                }
+            }
+        )+
+    };
+}
+
+impl_human_size_from!(u8, u16, u32, u64, u128, usize);
+
+impl fmt::Display for HumanSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = u128::from(self.0);
+
+        if bytes < 1024 {
+            return write!(f, "{bytes} B");
+        }
+
+        // The smallest unit where the value, rounded to tenths, is below 1024.
+        let (tenths, unit) = UNITS
+            .iter()
+            .enumerate()
+            .map(|(i, unit)| {
+                let shift = 10 * (i + 1);
+
+                ((bytes * 10 + (1 << (shift - 1))) >> shift, unit)
+            })
+            .find(|&(tenths, _)| tenths < 10_240)
+            .unwrap_or(((bytes * 10) >> 60, &UNITS[UNITS.len() - 1]));
+
+        write!(f, "{}.{} {unit}", tenths / 10, tenths % 10)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        let human = |bytes: u64| HumanSize::from(bytes).to_string();
+
+        assert_eq!(human(0), "0 B");
+        assert_eq!(human(1023), "1023 B");
+        assert_eq!(human(1024), "1.0 KiB");
+        assert_eq!(human(1536), "1.5 KiB");
+        assert_eq!(human(1_468_006), "1.4 MiB");
+        assert_eq!(human(1_048_575), "1.0 MiB");
+        assert_eq!(human(3 << 30), "3.0 GiB");
+        assert_eq!(human(u64::MAX), "16.0 EiB");
+        assert_eq!(HumanSize::from(2048_usize).to_string(), "2.0 KiB");
+    }
+}
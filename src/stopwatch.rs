@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::time::{Duration, Instant};
+
+/// Evaluates an expression (typically a block), logs how long it took, and returns its value.
+///
+/// Besides the wall-clock time, the CPU time used by the current thread is logged where it is
+/// available (on unix), which tells waiting and working apart.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_time;
+/// # fn parse(s: &str) -> usize { s.len() }
+/// #
+/// let ast = ddbg_time!("/tmp/log", "parse", { parse("1 + 2") });
+/// ```
+///
+/// This logs lines like `parse: 1.52ms (cpu 1.49ms)`.
+#[macro_export]
+macro_rules! ddbg_time {
+    ($uri:expr, $label:expr, $body:expr $(,)?) => {{
+        let stopwatch = $crate::Stopwatch::start();
+        let value = $body;
+
+        stopwatch.log($uri, ::std::file!(), ::std::line!(), $label);
+
+        value
+    }};
+}
+
+/// Evaluates an expression (typically a block) and returns its value, like
+/// [`ddbg_time!()`](crate::ddbg_time), but only logs if it took longer than the given budget.
+/// Useful in code that runs on a deadline, like an audio callback, to see only the slow
+/// iterations.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_deadline;
+/// # use std::time::Duration;
+/// # fn mix(buffer: &mut [f32]) {}
+/// # let mut buffer = [0.0; 256];
+/// #
+/// ddbg_deadline!("/tmp/log", Duration::from_millis(5), {
+///     mix(&mut buffer);
+/// });
+/// ```
+///
+/// This logs lines like `took 7.31ms (cpu 7.29ms), over the budget of 5ms`.
+#[macro_export]
+macro_rules! ddbg_deadline {
+    ($uri:expr, $budget:expr, $body:expr $(,)?) => {{
+        let stopwatch = $crate::Stopwatch::start();
+        let value = $body;
+
+        stopwatch.log_overrun($uri, ::std::file!(), ::std::line!(), $budget);
+
+        value
+    }};
+}
+
+/// Measures a [`ddbg_time!()`](crate::ddbg_time) or a [`ddbg_deadline!()`](crate::ddbg_deadline).
+#[doc(hidden)]
+pub struct Stopwatch {
+    start: Instant,
+    cpu_start: Option<Duration>,
+}
+
+impl Stopwatch {
+    #[must_use]
+    pub fn start() -> Stopwatch {
+        Stopwatch { start: Instant::now(), cpu_start: thread_cpu_time() }
+    }
+
+    pub fn log(self, uri: &'static str, file: &'static str, line: u32, label: &str) {
+        let elapsed = self.start.elapsed();
+        let cpu_elapsed =
+            self.cpu_start.zip(thread_cpu_time()).map(|(start, end)| end.saturating_sub(start));
+
+        if let Some(cpu_elapsed) = cpu_elapsed {
+            crate::dirty_log_message(
+                uri,
+                file,
+                line,
+                format_args!("{label}: {elapsed:.2?} (cpu {cpu_elapsed:.2?})"),
+            );
+        } else {
+            crate::dirty_log_message(uri, file, line, format_args!("{label}: {elapsed:.2?}"));
+        }
+    }
+
+    pub fn log_overrun(self, uri: &'static str, file: &'static str, line: u32, budget: Duration) {
+        let elapsed = self.start.elapsed();
+
+        if elapsed <= budget {
+            return;
+        }
+
+        let cpu_elapsed =
+            self.cpu_start.zip(thread_cpu_time()).map(|(start, end)| end.saturating_sub(start));
+
+        if let Some(cpu_elapsed) = cpu_elapsed {
+            crate::dirty_log_message(
+                uri,
+                file,
+                line,
+                format_args!(
+                    "took {elapsed:.2?} (cpu {cpu_elapsed:.2?}), over the budget of {budget:?}"
+                ),
+            );
+        } else {
+            crate::dirty_log_message(
+                uri,
+                file,
+                line,
+                format_args!("took {elapsed:.2?}, over the budget of {budget:?}"),
+            );
+        }
+    }
+}
+
+/// The CPU time used so far by the current thread.
+fn thread_cpu_time() -> Option<Duration> {
+    #[cfg(unix)]
+    {
+        let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+
+        // SAFETY: `time` is a valid `timespec` to write to.
+        let result = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+
+        if result == 0 {
+            let secs = u64::try_from(time.tv_sec).ok()?;
+            let nanos = u32::try_from(time.tv_nsec).ok()?;
+
+            return Some(Duration::new(secs, nanos));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,260 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::kv::Logfmt;
+use std::time::Duration;
+
+/// Logs the resource usage of the process: its resident and virtual memory, how many file
+/// descriptors (or handles, on Windows) it has open, and the CPU time it used so far.  This lets
+/// you correlate e.g. a memory leak with what else is logged.
+///
+/// It is logged as key-value pairs, like [`ddbg_kv!()`](crate::ddbg_kv).  What is not available
+/// on the platform is left out.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_sysinfo;
+/// #
+/// ddbg_sysinfo!("/tmp/log");
+/// ```
+///
+/// This logs lines like `rss_kib=10240 vm_kib=1048576 fds=12 cpu_user_ms=530 cpu_sys_ms=20`.
+#[macro_export]
+macro_rules! ddbg_sysinfo {
+    ($uri:expr $(,)?) => {{
+        $crate::dirty_log_sysinfo($uri, ::std::file!(), ::std::line!());
+    }};
+}
+
+/// The resource usage of the process.
+#[derive(Default)]
+struct SysInfo {
+    rss_kib: Option<u64>,
+    vm_kib: Option<u64>,
+    /// The peak resident memory, where the current one is not available.
+    max_rss_kib: Option<u64>,
+    fds: Option<usize>,
+    handles: Option<u32>,
+    cpu_user: Option<Duration>,
+    cpu_sys: Option<Duration>,
+}
+
+impl SysInfo {
+    #[cfg(unix)]
+    fn current() -> SysInfo {
+        let mut info = SysInfo::default();
+
+        // SAFETY: `rusage` is a plain C struct, for which all zeros is a valid value.
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+
+        // SAFETY: `usage` is a valid `rusage` to write to.
+        if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } == 0 {
+            let duration = |time: libc::timeval| {
+                let secs = u64::try_from(time.tv_sec).unwrap_or(0);
+                let micros = u32::try_from(time.tv_usec).unwrap_or(0);
+
+                Duration::new(secs, micros * 1000)
+            };
+
+            info.cpu_user = Some(duration(usage.ru_utime));
+            info.cpu_sys = Some(duration(usage.ru_stime));
+
+            // This is in bytes on Apple platforms and in KiB elsewhere.
+            let max_rss = u64::try_from(usage.ru_maxrss).unwrap_or(0);
+
+            info.max_rss_kib = match cfg!(target_vendor = "apple") {
+                true => Some(max_rss / 1024),
+                false => Some(max_rss),
+            };
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if let Some((vm_pages, rss_pages)) =
+            std::fs::read_to_string("/proc/self/statm").ok().and_then(|statm| parse_statm(&statm))
+        {
+            // SAFETY: This is always safe to call.
+            let page_size =
+                u64::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap_or(0);
+
+            info.vm_kib = Some(vm_pages * page_size / 1024);
+            info.rss_kib = Some(rss_pages * page_size / 1024);
+            info.max_rss_kib = None;
+        }
+
+        let fd_dir = match cfg!(any(target_os = "linux", target_os = "android")) {
+            true => "/proc/self/fd",
+            false => "/dev/fd",
+        };
+
+        // Reading the directory takes a file descriptor of its own, which we do not count.
+        info.fds = std::fs::read_dir(fd_dir).ok().map(|entries| entries.count().saturating_sub(1));
+
+        info
+    }
+
+    #[cfg(windows)]
+    fn current() -> SysInfo {
+        windows::current()
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn current() -> SysInfo {
+        SysInfo::default()
+    }
+
+    fn logfmt(&self) -> Logfmt {
+        let mut logfmt = Logfmt::new();
+        let millis = |duration: Duration| duration.as_millis();
+
+        let pairs: [(&str, Option<u128>); 7] = [
+            ("rss_kib", self.rss_kib.map(u128::from)),
+            ("max_rss_kib", self.max_rss_kib.map(u128::from)),
+            ("vm_kib", self.vm_kib.map(u128::from)),
+            ("fds", self.fds.and_then(|fds| u128::try_from(fds).ok())),
+            ("handles", self.handles.map(u128::from)),
+            ("cpu_user_ms", self.cpu_user.map(millis)),
+            ("cpu_sys_ms", self.cpu_sys.map(millis)),
+        ];
+
+        for (key, value) in pairs {
+            if let Some(value) = value {
+                logfmt.push_display(key, &value);
+            }
+        }
+
+        logfmt
+    }
+}
+
+/// Parses the total program size and the resident set size, in pages, from `/proc/self/statm`.
+#[cfg(any(target_os = "linux", target_os = "android", test))]
+fn parse_statm(statm: &str) -> Option<(u64, u64)> {
+    let mut fields = statm.split_whitespace().map(str::parse::<u64>);
+
+    match (fields.next(), fields.next()) {
+        (Some(Ok(vm)), Some(Ok(rss))) => Some((vm, rss)),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::SysInfo;
+    use std::ffi::c_void;
+    use std::time::Duration;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct FileTime {
+        low: u32,
+        high: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ProcessMemoryCounters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        working_set_size: usize,
+        quota_peak_paged_pool_usage: usize,
+        quota_paged_pool_usage: usize,
+        quota_peak_non_paged_pool_usage: usize,
+        quota_non_paged_pool_usage: usize,
+        pagefile_usage: usize,
+        peak_pagefile_usage: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn GetProcessTimes(
+            process: *mut c_void,
+            creation_time: *mut FileTime,
+            exit_time: *mut FileTime,
+            kernel_time: *mut FileTime,
+            user_time: *mut FileTime,
+        ) -> i32;
+        fn K32GetProcessMemoryInfo(
+            process: *mut c_void,
+            counters: *mut ProcessMemoryCounters,
+            cb: u32,
+        ) -> i32;
+        fn GetProcessHandleCount(process: *mut c_void, handle_count: *mut u32) -> i32;
+    }
+
+    /// Converts a `FILETIME` duration, in units of 100 nanoseconds.
+    fn duration(time: &FileTime) -> Duration {
+        let ticks = (u64::from(time.high) << 32) | u64::from(time.low);
+
+        Duration::from_nanos(ticks.saturating_mul(100))
+    }
+
+    pub(super) fn current() -> SysInfo {
+        let mut info = SysInfo::default();
+
+        // SAFETY: This is always safe to call, and returns a pseudo handle that needs no closing.
+        let process = unsafe { GetCurrentProcess() };
+
+        let mut times: [FileTime; 4] = Default::default();
+        let [creation, exit, kernel, user] = &mut times;
+
+        // SAFETY: All pointers are valid `FILETIME`s to write to.
+        if unsafe { GetProcessTimes(process, creation, exit, kernel, user) } != 0 {
+            info.cpu_user = Some(duration(user));
+            info.cpu_sys = Some(duration(kernel));
+        }
+
+        let mut counters = ProcessMemoryCounters::default();
+        let cb = u32::try_from(std::mem::size_of::<ProcessMemoryCounters>()).unwrap_or(0);
+
+        // SAFETY: `counters` is a valid `PROCESS_MEMORY_COUNTERS` of size `cb` to write to.
+        if unsafe { K32GetProcessMemoryInfo(process, &mut counters, cb) } != 0 {
+            info.rss_kib = u64::try_from(counters.working_set_size / 1024).ok();
+            info.vm_kib = u64::try_from(counters.pagefile_usage / 1024).ok();
+        }
+
+        let mut handles: u32 = 0;
+
+        // SAFETY: `handles` is a valid `u32` to write to.
+        if unsafe { GetProcessHandleCount(process, &mut handles) } != 0 {
+            info.handles = Some(handles);
+        }
+
+        info
+    }
+}
+
+/// Logs a `ddbg_sysinfo!()`.
+#[doc(hidden)]
+pub fn dirty_log_sysinfo(uri: &'static str, file: &'static str, line: u32) {
+    SysInfo::current().logfmt().log(uri, file, line);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_statm() {
+        assert_eq!(parse_statm("2500 610 420 1 0 300 0\n"), Some((2500, 610)));
+        assert_eq!(parse_statm("2500"), None);
+        assert_eq!(parse_statm(""), None);
+    }
+
+    #[test]
+    fn test_sysinfo_logfmt() {
+        let info = SysInfo {
+            rss_kib: Some(10_240),
+            fds: Some(7),
+            cpu_user: Some(Duration::from_micros(1_500_700)),
+            cpu_sys: Some(Duration::ZERO),
+            ..SysInfo::default()
+        };
+
+        assert_eq!(info.logfmt().line, "rss_kib=10240 fds=7 cpu_user_ms=1500 cpu_sys_ms=0");
+    }
+}
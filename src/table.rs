@@ -0,0 +1,153 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+
+/// Logs a collection as an aligned table, with a row per element and the given columns.  A column
+/// is either a field name, or a name and a closure that computes the cell from the element.  Each
+/// line of the table is logged as a message of its own, so they all keep the `[file:line]` prefix.
+///
+/// The cells are formatted with [`Debug`](std::fmt::Debug).  Anything with an `iter()` method works
+/// as the collection, e.g. a slice, an array, or a `Vec`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_table;
+/// # struct User { id: u32, name: &'static str, score: u32 }
+/// # let users = vec![User { id: 1, name: "ada", score: 37 }, User { id: 2, name: "bob", score: 5 }];
+/// #
+/// ddbg_table!("/tmp/log", users, id, name, double = |u| u.score * 2);
+/// ```
+///
+/// This logs:
+///
+/// ```text
+/// | id | name  | double |
+/// |----|-------|--------|
+/// | 1  | "ada" | 74     |
+/// | 2  | "bob" | 10     |
+/// ```
+#[macro_export]
+macro_rules! ddbg_table {
+    (@columns $table:ident, $rows:ident $(,)?) => {};
+    (@columns $table:ident, $rows:ident, $column:ident = $cell:expr $(, $($rest:tt)*)?) => {
+        $table.push_column(::std::stringify!($column), $rows.iter().map($cell));
+        $($crate::ddbg_table!(@columns $table, $rows, $($rest)*);)?
+    };
+    (@columns $table:ident, $rows:ident, $column:ident $(, $($rest:tt)*)?) => {
+        $table.push_column(::std::stringify!($column), $rows.iter().map(|row| &row.$column));
+        $($crate::ddbg_table!(@columns $table, $rows, $($rest)*);)?
+    };
+    ($uri:expr, $rows:expr, $($columns:tt)+) => {{
+        let rows = &$rows;
+        let mut table = $crate::Table::new();
+
+        $crate::ddbg_table!(@columns table, rows, $($columns)+);
+
+        table.log($uri, ::std::file!(), ::std::line!());
+    }};
+}
+
+/// The columns of a `ddbg_table!()`.
+#[doc(hidden)]
+pub struct Table {
+    /// The name and the cells of each column.
+    columns: Vec<(&'static str, Vec<String>)>,
+}
+
+impl Table {
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Table {
+        Table { columns: Vec::new() }
+    }
+
+    pub fn push_column<T: fmt::Debug>(
+        &mut self,
+        name: &'static str,
+        cells: impl Iterator<Item = T>,
+    ) {
+        let cells = cells.map(|cell| format!("{cell:?}")).collect();
+
+        self.columns.push((name, cells));
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .map(|(name, cells)| {
+                let cell_widths = cells.iter().map(|cell| cell.chars().count());
+
+                cell_widths.fold(name.chars().count(), usize::max)
+            })
+            .collect();
+        let row_count = self.columns.first().map_or(0, |(_, cells)| cells.len());
+
+        let row = |cells: Vec<&str>| -> String {
+            let mut line = String::from("|");
+
+            for (cell, &width) in cells.into_iter().zip(&widths) {
+                let padding = width - cell.chars().count();
+
+                line.push(' ');
+                line.push_str(cell);
+                line.extend(std::iter::repeat(' ').take(padding + 1));
+                line.push('|');
+            }
+
+            line
+        };
+
+        let mut lines = Vec::with_capacity(row_count + 2);
+
+        lines.push(row(self.columns.iter().map(|(name, _)| *name).collect()));
+        lines.push(widths.iter().fold(String::from("|"), |mut line, &width| {
+            line.extend(std::iter::repeat('-').take(width + 2));
+            line.push('|');
+            line
+        }));
+
+        for r in 0..row_count {
+            lines.push(row(self.columns.iter().map(|(_, cells)| cells[r].as_str()).collect()));
+        }
+
+        lines
+    }
+
+    pub fn log(self, uri: &'static str, file: &'static str, line: u32) {
+        for table_line in self.lines() {
+            crate::dirty_log_message(uri, file, line, format_args!("{table_line}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_table_lines() {
+        let mut table = Table::new();
+
+        table.push_column("n", [1, 200].iter());
+        table.push_column("name", ["ünï", "x"].iter());
+
+        assert_eq!(
+            table.lines(),
+            ["| n   | name  |", "|-----|-------|", "| 1   | \"ünï\" |", "| 200 | \"x\"   |",],
+        );
+    }
+
+    #[test]
+    fn test_table_lines_no_rows() {
+        let mut table = Table::new();
+
+        table.push_column("id", std::iter::empty::<u32>());
+
+        assert_eq!(table.lines(), ["| id |", "|----|"]);
+    }
+}
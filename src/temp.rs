@@ -0,0 +1,44 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use dashmap::DashMap;
+use std::sync::LazyLock;
+
+/// The file paths of the `ddbg_temp!()` labels, indexed by label.
+static DIRTY_TEMP_FILEPATHS: LazyLock<DashMap<&str, &str>> = LazyLock::new(DashMap::new);
+
+/// Writes a message to a file in the OS’s temporary directory.  Every label has its own file, and
+/// its full path is written to `stderr` the first time the label is used.
+///
+/// This is handy when you don’t care where the log goes, you just want a scratch destination.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_temp;
+/// #
+/// ddbg_temp!("parser", "Hello {}!", "world");
+/// ```
+#[macro_export]
+macro_rules! ddbg_temp {
+    ($label:literal, $($arg:tt)+) => {{
+        $crate::ddbg!($crate::dirty_temp_filepath($label), $($arg)+);
+    }};
+}
+
+/// Returns the file path for the given `ddbg_temp!()` label.
+#[doc(hidden)]
+pub fn dirty_temp_filepath(label: &'static str) -> &'static str {
+    *DIRTY_TEMP_FILEPATHS.entry(label).or_insert_with(|| {
+        let filepath = std::env::temp_dir().join(format!("dirty_debug_{label}.log"));
+
+        // This is done once per label, so it is fine to leak it.
+        let filepath: &'static str = Box::leak(filepath.display().to_string().into_boxed_str());
+
+        eprintln!("dirty-debug: logging \"{label}\" to {filepath}");
+
+        filepath
+    })
+}
@@ -0,0 +1,207 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::any::Any;
+use std::fmt;
+use std::sync::Once;
+
+fn log_to_active_sinks(args: fmt::Arguments<'_>) {
+    for uri in crate::sink::active_uris() {
+        crate::dirty_log_meta(uri, args);
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>")
+}
+
+fn log_exit() {
+    match crate::exit::status() {
+        Some(status) => log_to_active_sinks(format_args!("process exited with status {status}")),
+        None => log_to_active_sinks(format_args!("process exited")),
+    }
+}
+
+fn install_panic_hook() {
+    let previous_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        previous_hook(info);
+
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("<unnamed>");
+        let message = panic_message(info.payload());
+
+        match info.location() {
+            Some(location) => log_to_active_sinks(format_args!(
+                "thread '{thread_name}' panicked at {location}: {message}"
+            )),
+            None => log_to_active_sinks(format_args!("thread '{thread_name}' panicked: {message}")),
+        }
+    }));
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::ffi::c_int;
+    use std::sync::OnceLock;
+
+    /// Signals caused by the instruction being executed.  When the handler returns the instruction
+    /// runs again and raises the signal again, this time handled by the previous handler.
+    const FAULT_SIGNALS: [(c_int, &str); 5] = [
+        (libc::SIGSEGV, "SIGSEGV"),
+        (libc::SIGBUS, "SIGBUS"),
+        (libc::SIGILL, "SIGILL"),
+        (libc::SIGFPE, "SIGFPE"),
+        (libc::SIGABRT, "SIGABRT"),
+    ];
+
+    /// Signals sent to the process.  We only handle them if no one else does.
+    const TERMINATION_SIGNALS: [(c_int, &str); 4] = [
+        (libc::SIGTERM, "SIGTERM"),
+        (libc::SIGINT, "SIGINT"),
+        (libc::SIGHUP, "SIGHUP"),
+        (libc::SIGQUIT, "SIGQUIT"),
+    ];
+
+    /// The actions we replaced, so that we can restore them.
+    static PREVIOUS_ACTIONS: OnceLock<Vec<(c_int, libc::sigaction)>> = OnceLock::new();
+
+    fn signal_name(signal: c_int) -> &'static str {
+        FAULT_SIGNALS
+            .iter()
+            .chain(&TERMINATION_SIGNALS)
+            .find_map(|&(s, name)| (s == signal).then_some(name))
+            .unwrap_or("unknown")
+    }
+
+    extern "C" fn handle_signal(signal: c_int) {
+        // Logging is not async-signal-safe, so this is best effort: e.g. it deadlocks if the
+        // signal interrupted this thread while it was holding a lock we need.  We cannot let a
+        // panic unwind out of an `extern "C"` function.
+        let _result = std::panic::catch_unwind(|| {
+            super::log_to_active_sinks(format_args!(
+                "process terminated by signal {}",
+                signal_name(signal)
+            ));
+        });
+
+        let previous_action = PREVIOUS_ACTIONS
+            .get()
+            .and_then(|actions| actions.iter().find(|(s, _)| *s == signal))
+            .map(|(_, action)| action);
+
+        if let Some(action) = previous_action {
+            // SAFETY: `action` was returned by `sigaction()` for this signal.
+            unsafe { libc::sigaction(signal, action, std::ptr::null_mut()) };
+        }
+
+        if TERMINATION_SIGNALS.iter().any(|&(s, _)| s == signal) {
+            // SAFETY: This is always safe to call.
+            unsafe { libc::raise(signal) };
+        }
+    }
+
+    fn current_action(signal: c_int) -> Option<libc::sigaction> {
+        // SAFETY: `sigaction` is a plain C struct, for which all zeros is a valid value.
+        let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+
+        // SAFETY: `action` is a valid pointer.
+        let result = unsafe { libc::sigaction(signal, std::ptr::null(), &mut action) };
+
+        (result == 0).then_some(action)
+    }
+
+    pub(super) fn install_handlers() {
+        let mut previous_actions = Vec::new();
+
+        for &(signal, _) in &FAULT_SIGNALS {
+            if let Some(action) = current_action(signal) {
+                previous_actions.push((signal, action));
+            }
+        }
+
+        for &(signal, _) in &TERMINATION_SIGNALS {
+            if let Some(action) = current_action(signal).filter(|a| a.sa_sigaction == libc::SIG_DFL)
+            {
+                previous_actions.push((signal, action));
+            }
+        }
+
+        let previous_actions = PREVIOUS_ACTIONS.get_or_init(|| previous_actions);
+
+        for (signal, _) in previous_actions {
+            // SAFETY: `sigaction` is a plain C struct, for which all zeros is a valid value.
+            let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+
+            action.sa_sigaction = handle_signal as extern "C" fn(c_int) as libc::sighandler_t;
+            // Run on the alternate stack (if any), so we can report stack overflows.
+            action.sa_flags = libc::SA_ONSTACK;
+
+            // SAFETY: `action` is a valid action with a handler that does not return an error.
+            unsafe { libc::sigaction(*signal, &action, std::ptr::null_mut()) };
+        }
+    }
+}
+
+/// Reports how the process terminated to every destination that was logged to, as the last
+/// line of each of them.  This tells apart a process that exited normally (with its exit status,
+/// where the platform tells us), from one that panicked or was killed by a signal:
+///
+/// ```text
+/// [dirty-debug] thread 'main' panicked at src/main.rs:42:5: index out of bounds
+/// [dirty-debug] process exited with status 101
+/// ```
+///
+/// The termination is reported on a best-effort basis: signals are only handled on unix, and
+/// nothing can be reported if the process is killed with `SIGKILL`.  Signals the program already
+/// handles itself, such as `SIGINT`, are left alone.
+///
+/// Calling this more than once has no effect.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg;
+/// #
+/// dirty_debug::report_termination();
+///
+/// ddbg!("/tmp/log", "Starting up");
+/// ```
+pub fn report_termination() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        crate::exit::on_exit_last(log_exit);
+        install_panic_hook();
+
+        #[cfg(unix)]
+        signals::install_handlers();
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_panic_message() {
+        let payload = std::panic::catch_unwind(|| panic!("static message")).unwrap_err();
+
+        assert_eq!(panic_message(&*payload), "static message");
+
+        let payload = std::panic::catch_unwind(|| panic!("formatted {}", 42)).unwrap_err();
+
+        assert_eq!(panic_message(&*payload), "formatted 42");
+
+        let payload = std::panic::catch_unwind(|| std::panic::panic_any(42)).unwrap_err();
+
+        assert_eq!(panic_message(&*payload), "Box<dyn Any>");
+    }
+}
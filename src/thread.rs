@@ -0,0 +1,102 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use crate::kv::Logfmt;
+use std::fmt;
+
+/// Logs which thread it runs on: its name, its Rust [`ThreadId`](std::thread::ThreadId), and the
+/// thread id of the operating system (where available), after an optional message.  Useful when
+/// your code is called from a thread pool you know nothing about.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_thread;
+/// # let request_id = 7;
+/// #
+/// ddbg_thread!("/tmp/log");
+/// ddbg_thread!("/tmp/log", "handling request {}", request_id);
+/// ```
+///
+/// This logs lines like `handling request 7: name=tokio-runtime-worker id=ThreadId(3) tid=4127`.
+#[macro_export]
+macro_rules! ddbg_thread {
+    ($uri:expr $(,)?) => {{
+        $crate::dirty_log_thread($uri, ::std::file!(), ::std::line!(), ::std::option::Option::None);
+    }};
+    ($uri:expr, $($arg:tt)+) => {{
+        $crate::dirty_log_thread(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::option::Option::Some(::std::format_args!($($arg)+)),
+        );
+    }};
+}
+
+/// The id of the current thread in the operating system.
+// On some platforms this is always available.
+#[allow(clippy::unnecessary_wraps)]
+pub(crate) fn os_thread_id() -> Option<u64> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        // SAFETY: This is always safe to call.
+        let tid = unsafe { libc::gettid() };
+
+        u64::try_from(tid).ok()
+    }
+
+    #[cfg(target_vendor = "apple")]
+    {
+        let mut tid: u64 = 0;
+
+        // SAFETY: A null thread means the current thread, and `tid` is a valid `u64` to write to.
+        let result = unsafe { libc::pthread_threadid_np(0, &mut tid) };
+
+        (result == 0).then_some(tid)
+    }
+
+    #[cfg(windows)]
+    {
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn GetCurrentThreadId() -> u32;
+        }
+
+        // SAFETY: This is always safe to call.
+        Some(u64::from(unsafe { GetCurrentThreadId() }))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple", windows)))]
+    {
+        None
+    }
+}
+
+/// Logs a `ddbg_thread!()`.
+#[doc(hidden)]
+pub fn dirty_log_thread(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    args: Option<fmt::Arguments<'_>>,
+) {
+    let thread = std::thread::current();
+    let mut logfmt = Logfmt::new();
+
+    logfmt.push_display("name", thread.name().unwrap_or("<unnamed>"));
+    logfmt.push_debug("id", &thread.id());
+
+    if let Some(tid) = os_thread_id() {
+        logfmt.push_display("tid", &tid);
+    }
+
+    match args {
+        Some(args) => {
+            crate::dirty_log_message(uri, file, line, format_args!("{args}: {}", logfmt.line));
+        }
+        None => logfmt.log(uri, file, line),
+    }
+}
@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime};
+
+/// When the process first asked for [`since_start()`].
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// The current time as a duration since the unix epoch.
+pub(crate) fn since_epoch() -> Duration {
+    // If the clock is before the epoch there is not much we can do.
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default()
+}
+
+/// The time elapsed since the first call to this function.  Unlike the wall-clock time, this is
+/// monotonic, so it is not affected by adjustments of the system clock.
+pub(crate) fn since_start() -> Duration {
+    START.elapsed()
+}
+
+/// A point in time in UTC, broken down into its calendar components.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct UtcDateTime {
+    pub(crate) year: i64,
+    pub(crate) month: u32,
+    pub(crate) day: u32,
+    pub(crate) hour: u32,
+    pub(crate) minute: u32,
+    pub(crate) second: u32,
+    pub(crate) nanosecond: u32,
+}
+
+impl UtcDateTime {
+    pub(crate) fn from_since_epoch(since_epoch: Duration) -> UtcDateTime {
+        let secs = since_epoch.as_secs();
+        let days = i64::try_from(secs / 86_400).unwrap_or(i64::MAX);
+        let secs_of_day = u32::try_from(secs % 86_400).unwrap_or(0);
+
+        // Converts days since the epoch to a civil date.  This is Howard Hinnant’s
+        // `civil_from_days()` algorithm.  See <https://howardhinnant.github.io/date_algorithms.html>.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        UtcDateTime {
+            year,
+            // These are always in range, so the conversions never fail.
+            month: u32::try_from(month).unwrap_or(1),
+            day: u32::try_from(day).unwrap_or(1),
+            hour: secs_of_day / 3600,
+            minute: secs_of_day / 60 % 60,
+            second: secs_of_day % 60,
+            nanosecond: since_epoch.subsec_nanos(),
+        }
+    }
+
+    pub(crate) fn now() -> UtcDateTime {
+        UtcDateTime::from_since_epoch(since_epoch())
+    }
+}
+
+/// Formats the date and time in RFC 3339, with microseconds, e.g. `2026-10-16T12:34:56.123456Z`.
+impl fmt::Display for UtcDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            self.nanosecond / 1000,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_utc_date_time_from_since_epoch() {
+        assert_eq!(
+            UtcDateTime::from_since_epoch(Duration::ZERO),
+            UtcDateTime {
+                year: 1970,
+                month: 1,
+                day: 1,
+                hour: 0,
+                minute: 0,
+                second: 0,
+                nanosecond: 0
+            }
+        );
+        assert_eq!(
+            UtcDateTime::from_since_epoch(Duration::new(951_827_696, 123)),
+            UtcDateTime {
+                year: 2000,
+                month: 2,
+                day: 29,
+                hour: 12,
+                minute: 34,
+                second: 56,
+                nanosecond: 123
+            }
+        );
+        assert_eq!(
+            UtcDateTime::from_since_epoch(Duration::from_secs(1_798_761_599)),
+            UtcDateTime {
+                year: 2026,
+                month: 12,
+                day: 31,
+                hour: 23,
+                minute: 59,
+                second: 59,
+                nanosecond: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_utc_date_time_display() {
+        let date_time = UtcDateTime::from_since_epoch(Duration::new(951_827_696, 123_456_789));
+
+        assert_eq!(date_time.to_string(), "2000-02-29T12:34:56.123456Z");
+        assert_eq!(
+            UtcDateTime::from_since_epoch(Duration::ZERO).to_string(),
+            "1970-01-01T00:00:00.000000Z"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+//! Chrome trace event files, which can be opened with Perfetto (<https://ui.perfetto.dev>) or
+//! `about://tracing`.  See <https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU>.
+
+use crate::json::push_string;
+use crate::sink::append_entry;
+use dashmap::DashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// Files we are writing trace events to, indexed by their path.
+static DIRTY_TRACE_FILES: LazyLock<DashMap<&str, File>> = LazyLock::new(DashMap::new);
+
+/// Writes a span to a Chrome trace event file when the returned [`Span`] is dropped, so that it
+/// covers the rest of the scope.  The file can be opened with [Perfetto](https://ui.perfetto.dev)
+/// or `about://tracing` to see a timeline of the spans of every thread.
+///
+/// The trace file is separate from the text logs, and is given as a file path.  Spans that do not
+/// fit a scope can be written with [`ddbg_span_begin!()`](crate::ddbg_span_begin) and
+/// [`ddbg_span_end!()`](crate::ddbg_span_end) instead.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_span;
+/// #
+/// fn parse() {
+///     let _span = ddbg_span!("/tmp/trace.json", "parse");
+///
+///     // …
+/// }
+/// ```
+#[macro_export]
+macro_rules! ddbg_span {
+    ($trace_filepath:expr, $name:expr $(,)?) => {
+        $crate::Span::start($trace_filepath, ::std::file!(), ::std::line!(), $name)
+    };
+}
+
+/// Writes the beginning of a span to a Chrome trace event file.  It must be ended by a
+/// [`ddbg_span_end!()`](crate::ddbg_span_end) with the same name on the same thread.
+///
+/// See [`ddbg_span!()`](crate::ddbg_span) for spans that cover a scope.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::{ddbg_span_begin, ddbg_span_end};
+/// #
+/// ddbg_span_begin!("/tmp/trace.json", "frame");
+/// // …
+/// ddbg_span_end!("/tmp/trace.json", "frame");
+/// ```
+#[macro_export]
+macro_rules! ddbg_span_begin {
+    ($trace_filepath:expr, $name:expr $(,)?) => {{
+        $crate::dirty_trace_event($trace_filepath, ::std::file!(), ::std::line!(), $name, 'B');
+    }};
+}
+
+/// Writes the end of a span started by [`ddbg_span_begin!()`](crate::ddbg_span_begin).
+#[macro_export]
+macro_rules! ddbg_span_end {
+    ($trace_filepath:expr, $name:expr $(,)?) => {{
+        $crate::dirty_trace_event($trace_filepath, ::std::file!(), ::std::line!(), $name, 'E');
+    }};
+}
+
+/// A span of a [`ddbg_span!()`](crate::ddbg_span), which is written when this is dropped.
+#[must_use = "the span ends when it is dropped"]
+pub struct Span {
+    trace_filepath: &'static str,
+    file: &'static str,
+    line: u32,
+    name: &'static str,
+    start: Instant,
+    start_since_epoch: Duration,
+}
+
+impl Span {
+    #[doc(hidden)]
+    pub fn start(
+        trace_filepath: &'static str,
+        file: &'static str,
+        line: u32,
+        name: &'static str,
+    ) -> Span {
+        let start_since_epoch = crate::time::since_epoch();
+
+        Span { trace_filepath, file, line, name, start: Instant::now(), start_since_epoch }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        let event =
+            event(self.file, self.line, self.name, 'X', self.start_since_epoch, Some(duration));
+
+        write_event(self.trace_filepath, &event);
+    }
+}
+
+/// Formats a trace event, followed by a comma and a newline.
+fn event(
+    file: &str,
+    line: u32,
+    name: &str,
+    phase: char,
+    timestamp: Duration,
+    duration: Option<Duration>,
+) -> String {
+    let mut event = String::with_capacity(192);
+    let tid = crate::thread::os_thread_id().unwrap_or(0);
+
+    event.push_str(r#"{"name":"#);
+    push_string(&mut event, name);
+    let _ = write!(
+        event,
+        r#","ph":"{phase}","pid":{},"tid":{tid},"ts":{}"#,
+        std::process::id(),
+        timestamp.as_micros(),
+    );
+
+    if let Some(duration) = duration {
+        let _ = write!(event, r#","dur":{}"#, duration.as_micros());
+    }
+
+    event.push_str(r#","args":{"location":"#);
+    push_string(&mut event, &format!("{file}:{line}"));
+    event.push_str("}},\n");
+
+    event
+}
+
+fn write_event(trace_filepath: &'static str, event: &str) {
+    let result = DIRTY_TRACE_FILES
+        .entry(trace_filepath)
+        .or_try_insert_with(|| {
+            let filepath = trace_filepath.strip_prefix("file://").unwrap_or(trace_filepath);
+            let mut file = File::options().create(true).append(true).open(filepath)?;
+
+            // The closing bracket is optional in the trace event format, so the file is valid
+            // even if the process crashes.
+            if file.metadata()?.len() == 0 {
+                file.write_all(b"[\n")?;
+            }
+
+            Ok::<_, io::Error>(file)
+        })
+        .and_then(|mut file| append_entry(&mut file, event.as_bytes()));
+
+    if let Err(e) = result {
+        crate::report_failure(trace_filepath, &e);
+    }
+}
+
+/// Writes a `ddbg_span_begin!()` or `ddbg_span_end!()`.
+#[doc(hidden)]
+pub fn dirty_trace_event(
+    trace_filepath: &'static str,
+    file: &'static str,
+    line: u32,
+    name: &str,
+    phase: char,
+) {
+    let event = event(file, line, name, phase, crate::time::since_epoch(), None);
+
+    write_event(trace_filepath, &event);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_event() {
+        let event = event(
+            "src/main.rs",
+            7,
+            "parse \"x\"",
+            'X',
+            Duration::from_micros(1_500),
+            Some(Duration::from_nanos(2_999)),
+        );
+        let expected = format!(
+            "{{\"name\":\"parse \\\"x\\\"\",\"ph\":\"X\",\"pid\":{},\"tid\":{},\"ts\":1500,\"dur\":2,\
+             \"args\":{{\"location\":\"src/main.rs:7\"}}}},\n",
+            std::process::id(),
+            crate::thread::os_thread_id().unwrap_or(0),
+        );
+
+        assert_eq!(event, expected);
+    }
+}
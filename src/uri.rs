@@ -0,0 +1,66 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::io;
+use std::str::FromStr;
+
+/// The query parameters of an uri, e.g. `baud=115200&parity=even`.
+#[derive(Clone, Copy)]
+pub(crate) struct Query<'a> {
+    query: &'a str,
+}
+
+impl<'a> Query<'a> {
+    /// Splits `target` into the part before the `?` and its query parameters.
+    pub(crate) fn split(target: &'a str) -> (&'a str, Query<'a>) {
+        let (target, query) = target.split_once('?').unwrap_or((target, ""));
+
+        (target, Query { query })
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&'a str> {
+        self.query
+            .split('&')
+            .filter_map(|param| param.split_once('=').or(Some((param, ""))))
+            .find_map(|(key, value)| (key == name).then_some(value))
+    }
+
+    pub(crate) fn get_parsed<T: FromStr>(&self, name: &str) -> io::Result<Option<T>> {
+        self.get(name)
+            .map(|value| {
+                T::from_str(value).map_err(|_| {
+                    let msg = format!("invalid value for query parameter \"{name}\": \"{value}\"");
+                    io::Error::new(io::ErrorKind::InvalidInput, msg)
+                })
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let (target, query) = Query::split("/dev/ttyUSB0?baud=115200&flag&parity=even");
+
+        assert_eq!(target, "/dev/ttyUSB0");
+        assert_eq!(query.get("baud"), Some("115200"));
+        assert_eq!(query.get("parity"), Some("even"));
+        assert_eq!(query.get("flag"), Some(""));
+        assert_eq!(query.get("missing"), None);
+        assert_eq!(query.get_parsed::<u32>("baud").unwrap(), Some(115_200));
+        assert!(query.get_parsed::<u32>("parity").is_err());
+    }
+
+    #[test]
+    fn test_query_empty() {
+        let (target, query) = Query::split("/tmp/log");
+
+        assert_eq!(target, "/tmp/log");
+        assert_eq!(query.get("baud"), None);
+    }
+}
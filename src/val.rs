@@ -0,0 +1,121 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+
+/// Logs an expression and its value, and returns the value, like [`dbg!()`](std::dbg).  This
+/// lets you log a value in the middle of an expression:
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_val;
+/// # let (width, height) = (3, 4);
+/// #
+/// let area = ddbg_val!("/tmp/log", width * height) / 2;
+/// ```
+///
+/// This logs `width * height = 12`.  The value is formatted with [`Debug`](std::fmt::Debug), and
+/// it is moved in and out, so use a reference to log a value that is not `Copy` without moving it.
+/// With more than one expression a tuple of the values is returned.
+#[macro_export]
+macro_rules! ddbg_val {
+    ($uri:expr, $val:expr $(,)?) => {
+        // `match` keeps the temporaries of `$val` alive, like `dbg!()` does.
+        match $val {
+            value => {
+                $crate::ddbg!($uri, "{} = {:?}", ::std::stringify!($val), &value);
+                value
+            }
+        }
+    };
+    ($uri:expr, $($val:expr),+ $(,)?) => {
+        ($($crate::ddbg_val!($uri, $val)),+,)
+    };
+}
+
+/// Evaluates a [`Result`], logs the error if it is an `Err`, and returns the result unchanged, so
+/// that it composes with `?`:
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_err;
+/// # fn f() -> std::io::Result<()> {
+/// let config = ddbg_err!("/tmp/log", std::fs::read_to_string("config.toml"))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// On failure this logs lines like
+/// `std::fs::read_to_string("config.toml") failed: Os { code: 2, kind: NotFound, … }`.  The error is
+/// formatted with [`Debug`](std::fmt::Debug).  Nothing is logged on success.
+#[macro_export]
+macro_rules! ddbg_err {
+    ($uri:expr, $result:expr $(,)?) => {
+        match $result {
+            result => {
+                if let ::std::result::Result::Err(error) = &result {
+                    $crate::dirty_log_error(
+                        $uri,
+                        ::std::file!(),
+                        ::std::line!(),
+                        ::std::format_args!("{} failed: {:?}", ::std::stringify!($result), error),
+                    );
+                }
+
+                result
+            }
+        }
+    };
+}
+
+/// Like [`ddbg_val!()`](crate::ddbg_val), but the value is pretty-printed (with `{:#?}`).  Each
+/// line of the output is logged as a message of its own, so they all keep the `[file:line]` prefix:
+///
+/// ```text
+/// [src/main.rs:7] config = Config {
+/// [src/main.rs:7]     port: 8080,
+/// [src/main.rs:7]     verbose: true,
+/// [src/main.rs:7] }
+/// ```
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_pretty;
+/// # let config = vec![(8080, true)];
+/// #
+/// ddbg_pretty!("/tmp/log", &config);
+/// ```
+#[macro_export]
+macro_rules! ddbg_pretty {
+    ($uri:expr, $val:expr $(,)?) => {
+        match $val {
+            value => {
+                $crate::dirty_log_pretty(
+                    $uri,
+                    ::std::file!(),
+                    ::std::line!(),
+                    ::std::stringify!($val),
+                    &value,
+                );
+                value
+            }
+        }
+    };
+}
+
+/// Logs a `ddbg_pretty!()`.
+#[doc(hidden)]
+pub fn dirty_log_pretty<T: fmt::Debug + ?Sized>(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    value: &T,
+) {
+    let pretty: String = format!("{label} = {value:#?}");
+
+    for pretty_line in pretty.lines() {
+        crate::dirty_log_message(uri, file, line, format_args!("{pretty_line}"));
+    }
+}
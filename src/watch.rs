@@ -0,0 +1,113 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Logs the value of an expression periodically, from a background thread, until the returned
+/// [`Watch`] is dropped.  This lets you see how something evolves while the rest of the program is
+/// busy.
+///
+/// The expression is given as a closure, which must be `Send` and `'static`, and its value is
+/// formatted with [`Debug`](std::fmt::Debug).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_watch;
+/// # use std::sync::{Arc, Mutex};
+/// # use std::time::Duration;
+/// # let queue: Arc<Mutex<Vec<u32>>> = Arc::default();
+/// #
+/// let _watch = {
+///     let queue = Arc::clone(&queue);
+///
+///     ddbg_watch!("/tmp/log", Duration::from_millis(500), || queue.lock().unwrap().len())
+/// };
+/// ```
+///
+/// This logs `queue.lock().unwrap().len() = 42` every half a second.
+#[macro_export]
+macro_rules! ddbg_watch {
+    ($uri:expr, $interval:expr, $(move)? || $value:expr $(,)?) => {
+        $crate::Watch::start(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($value),
+            $interval,
+            move || $value,
+        )
+    };
+    ($uri:expr, $interval:expr, $f:expr $(,)?) => {
+        $crate::Watch::start(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($f),
+            $interval,
+            $f,
+        )
+    };
+}
+
+/// Stops the background logging of a [`ddbg_watch!()`](crate::ddbg_watch) when dropped.
+#[must_use = "the watch stops when it is dropped"]
+pub struct Watch {
+    /// Dropping this wakes the thread up so that it stops.
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watch {
+    #[doc(hidden)]
+    pub fn start<T: fmt::Debug>(
+        uri: &'static str,
+        file: &'static str,
+        line: u32,
+        label: &'static str,
+        interval: Duration,
+        mut value: impl FnMut() -> T + Send + 'static,
+    ) -> Watch {
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let thread =
+            std::thread::Builder::new().name("dirty-debug-watch".to_owned()).spawn(move || loop {
+                crate::dirty_log_message(uri, file, line, format_args!("{label} = {:?}", value()));
+
+                match stopped.recv_timeout(interval) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => (),
+                    Ok(()) | Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            });
+
+        let thread = match thread {
+            Ok(thread) => Some(thread),
+            Err(e) => {
+                crate::dirty_log_message(
+                    uri,
+                    file,
+                    line,
+                    format_args!("cannot watch {label}: failed to spawn thread: {e}"),
+                );
+                None
+            }
+        };
+
+        Watch { stop: Some(stop), thread }
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::io::Write;
+use std::time::Duration;
+
+/// Writes a buffer of audio samples to a WAV file of its own, and logs its path, so that you can
+/// listen to it.  The samples are `f32` or `i16`, and the sample rate is given in Hz.  With more
+/// than one channel the samples are interleaved.
+///
+/// As with [`ddbg_bytes!()`](crate::ddbg_bytes), the `{n}` in the file path is replaced by a
+/// number that makes it a new file.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use dirty_debug::ddbg_wav;
+/// # let (buffer, stereo_buffer) = (vec![0.0_f32; 512], vec![0_i16; 1024]);
+/// #
+/// ddbg_wav!("/tmp/log", "/tmp/block_{n}.wav", &buffer, 48_000);
+/// ddbg_wav!("/tmp/log", "/tmp/out_{n}.wav", &stereo_buffer, 44_100, channels = 2);
+/// ```
+///
+/// This logs lines like `&buffer: 512 frames (10.67ms at 48000 Hz) written to "/tmp/block_0.wav"`.
+#[macro_export]
+macro_rules! ddbg_wav {
+    ($uri:expr, $filepath:expr, $samples:expr, $sample_rate:expr $(,)?) => {
+        $crate::ddbg_wav!($uri, $filepath, $samples, $sample_rate, channels = 1)
+    };
+    ($uri:expr, $filepath:expr, $samples:expr, $sample_rate:expr, channels = $channels:expr $(,)?) => {{
+        $crate::dirty_log_wav(
+            $uri,
+            ::std::file!(),
+            ::std::line!(),
+            ::std::stringify!($samples),
+            ::std::convert::AsRef::<str>::as_ref(&$filepath),
+            ::std::convert::AsRef::<[_]>::as_ref(&$samples),
+            $sample_rate,
+            $channels,
+        );
+    }};
+}
+
+/// A type of sample that can be written with [`ddbg_wav!()`](crate::ddbg_wav): `f32` (in the
+/// range -1 to 1) or `i16`.
+pub trait WavSample: Copy {
+    #[doc(hidden)]
+    const FORMAT_TAG: u16;
+    #[doc(hidden)]
+    const BITS: u16;
+
+    #[doc(hidden)]
+    fn write_le_bytes(self, out: &mut Vec<u8>);
+}
+
+impl WavSample for f32 {
+    /// `WAVE_FORMAT_IEEE_FLOAT`.
+    const FORMAT_TAG: u16 = 3;
+    const BITS: u16 = 32;
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl WavSample for i16 {
+    /// `WAVE_FORMAT_PCM`.
+    const FORMAT_TAG: u16 = 1;
+    const BITS: u16 = 16;
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+/// Encodes the samples as a WAV file.
+fn wav<S: WavSample>(samples: &[S], sample_rate: u32, channels: u16) -> Vec<u8> {
+    let block_align = channels * (S::BITS / 8);
+    let data_len = u32::try_from(samples.len() * usize::from(S::BITS / 8)).unwrap_or(u32::MAX);
+    let mut wav = Vec::with_capacity(44 + samples.len() * usize::from(S::BITS / 8));
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36_u32.saturating_add(data_len)).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16_u32.to_le_bytes());
+    wav.extend_from_slice(&S::FORMAT_TAG.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&S::BITS.to_le_bytes());
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+
+    for &sample in samples {
+        sample.write_le_bytes(&mut wav);
+    }
+
+    wav
+}
+
+/// Logs a `ddbg_wav!()`.
+#[doc(hidden)]
+#[allow(clippy::too_many_arguments)]
+pub fn dirty_log_wav<S: WavSample>(
+    uri: &'static str,
+    file: &'static str,
+    line: u32,
+    label: &str,
+    filepath_template: &str,
+    samples: &[S],
+    sample_rate: u32,
+    channels: u16,
+) {
+    let channels = channels.max(1);
+    let frames = samples.len() / usize::from(channels);
+    let duration = Duration::from_secs_f64(
+        f64::from(u32::try_from(frames).unwrap_or(u32::MAX)) / f64::from(sample_rate.max(1)),
+    );
+
+    let result =
+        crate::bytes::create_dump_file(filepath_template).and_then(|(mut dump, filepath)| {
+            dump.write_all(&wav(samples, sample_rate, channels)).map(|()| filepath)
+        });
+
+    match result {
+        Ok(filepath) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!(
+                "{label}: {frames} frames ({duration:.2?} at {sample_rate} Hz) written to \
+                 \"{filepath}\""
+            ),
+        ),
+        Err(e) => crate::dirty_log_message(
+            uri,
+            file,
+            line,
+            format_args!("{label}: cannot write audio to \"{filepath_template}\": {e}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wav_i16() {
+        let wav = wav(&[1_i16, -2, 3, -4], 8000, 2);
+
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(wav[4..8], (36_u32 + 8).to_le_bytes());
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(wav[20..22], 1_u16.to_le_bytes());
+        assert_eq!(wav[22..24], 2_u16.to_le_bytes());
+        assert_eq!(wav[24..28], 8000_u32.to_le_bytes());
+        assert_eq!(wav[28..32], 32_000_u32.to_le_bytes());
+        assert_eq!(wav[32..34], 4_u16.to_le_bytes());
+        assert_eq!(wav[34..36], 16_u16.to_le_bytes());
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(wav[40..44], 8_u32.to_le_bytes());
+        assert_eq!(wav[44..], [1, 0, 0xfe, 0xff, 3, 0, 0xfc, 0xff]);
+    }
+
+    #[test]
+    fn test_wav_f32() {
+        let wav = wav(&[0.5_f32], 48_000, 1);
+
+        assert_eq!(wav[20..22], 3_u16.to_le_bytes());
+        assert_eq!(wav[34..36], 32_u16.to_le_bytes());
+        assert_eq!(wav[44..], 0.5_f32.to_le_bytes());
+    }
+}